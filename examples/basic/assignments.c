@@ -0,0 +1,29 @@
+int assignments(int x, int *values, long *totals, unsigned char *bytes)
+{
+    int a, b;
+
+    // Assignments group right to left, and have the value that was stored
+    a = b = x + 1;
+
+    a += 3;
+    a -= b;
+    a *= 4;
+    a /= 2;
+    a %= 7;
+    a <<= 2;
+    a >>= 1;
+    a &= 12;
+    a |= 1;
+    a ^= b;
+
+    // Stores through pointers are sized by the type pointed to
+    *values = a;
+    values[2] += *values;
+    totals[1] -= 1;
+    bytes[x] >>= 1;
+    totals += 2;
+
+    ;
+
+    return a++ + --b;
+}
@@ -0,0 +1,10 @@
+// C23 keywords are ordinary identifiers in C17, which is the default standard
+#define true 1
+#if true
+int main()
+{
+    int bool = true;
+    int typeof = 2;
+    return bool + typeof;
+}
+#endif
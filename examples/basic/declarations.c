@@ -0,0 +1,18 @@
+int declarations(int x)
+{
+    int a = 3, b = a + x;
+    int *pointer, c;
+
+    {
+        // Names declared in a block shadow those outside it
+        int a = b * 2;
+
+        if (a)
+        {
+            int b = a + 1;
+            return b;
+        }
+    }
+
+    return a + b;
+}
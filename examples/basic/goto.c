@@ -0,0 +1,62 @@
+int checked_sum(int *values, int count)
+{
+    int total = 0;
+
+    if (count < 0)
+        goto fail;
+
+    for (int i = 0; i < count; i++)
+    {
+        // Jumping forward to a label which hasn't been seen yet
+        if (values[i] < 0)
+            goto fail;
+
+        total += values[i];
+    }
+
+    return total;
+
+fail:
+    return 0;
+}
+
+int countdown(int n)
+{
+    int steps = 0;
+
+again:
+    if (n > 0)
+    {
+        n--;
+        steps++;
+        goto again;
+    }
+
+    return steps;
+}
+
+// Labels as values, a GNU extension, let interpreters dispatch with computed gotos
+int run(char *program, void **handlers)
+{
+    int accumulator = 0;
+    int pc = 0;
+
+    handlers[0] = &&halt;
+    handlers[1] = &&increment;
+    handlers[2] = &&double_it;
+
+    goto *handlers[program[pc]];
+
+increment:
+    accumulator++;
+    pc++;
+    goto *handlers[program[pc]];
+
+double_it:
+    accumulator <<= 1;
+    pc++;
+    goto *handlers[program[pc]];
+
+halt:
+    return accumulator;
+}
@@ -0,0 +1,13 @@
+// Values which are still needed after a call are backed up around it, as the called function may overwrite them
+int square(int y)
+{
+    return y * y;
+}
+
+int main()
+{
+    int x = 3;
+    int a = x * 2;
+    square(x);
+    return a + x;
+}
@@ -0,0 +1,50 @@
+int sum_skipping_multiples(int limit, int skip)
+{
+    int total = 0;
+
+    for (int i = 0; i < limit; i++)
+    {
+        // Continuing still runs the step
+        if (i % skip == 0)
+            continue;
+
+        total += i;
+    }
+
+    return total;
+}
+
+int first_power_above(int bound)
+{
+    int power = 1;
+
+    do
+    {
+        power *= 2;
+    } while (power <= bound);
+
+    return power;
+}
+
+int count_down(int n)
+{
+    int steps = 0;
+
+    for (;;)
+    {
+        while (n > 100)
+        {
+            n -= 100;
+            steps++;
+        }
+
+        // Breaking only leaves the innermost loop
+        if (n <= 0)
+            break;
+
+        n--;
+        steps++;
+    }
+
+    return steps;
+}
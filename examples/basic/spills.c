@@ -0,0 +1,18 @@
+// More values are live throughout the loop than there are registers, so some are kept on the stack
+int f(int n)
+{
+    int a = 1; int b = 2; int c = 3; int d = 4; int e = 5; int g = 6; int h = 7; int i = 8;
+    int j = 9; int k = 10; int l = 11; int m = 12; int o = 13; int p = 14; int q = 15; int r = 16;
+    while (n)
+    {
+        a = a + b; b = b + c; c = c + d; d = d + e; e = e + g; g = g + h; h = h + i; i = i + j;
+        j = j + k; k = k + l; l = l + m; m = m + o; o = o + p; p = p + q; q = q + r; r = r + a;
+        n = n - 1;
+    }
+    return a + b + c + d + e + g + h + i + j + k + l + m + o + p + q + r;
+}
+
+int main()
+{
+    return f(3) + f(4);
+}
@@ -0,0 +1,66 @@
+#define OPCODE_BASE 0x40
+
+// Dense cases are dispatched through a jump table
+int decode_opcode(int opcode, int operand)
+{
+    int result = 0;
+
+    switch (opcode - OPCODE_BASE)
+    {
+        case 0:
+            result = operand;
+            break;
+        case 1:
+            result = operand + 1;
+            break;
+        case 2:
+            // Falls through to the next case
+            operand <<= 1;
+        case 3:
+            result = operand * 3;
+            break;
+        case 5:
+            return operand;
+        default:
+            result = 0;
+            break;
+    }
+
+    return result;
+}
+
+// Sparse cases are found with a binary search
+int message_length(int message_type)
+{
+    switch (message_type)
+    {
+        case 0x0800: return 20;
+        case 0x0806: return 28;
+        case 0x86DD: return 40;
+        case 0x8100: return 4;
+        case 0x88CC: return 14;
+        case 1 << 16: return 0;
+    }
+
+    return 0;
+}
+
+// A few cases are compared one at a time
+int count_vowels(char *text, int length)
+{
+    int vowels = 0;
+
+    for (int i = 0; i < length; i++)
+    {
+        switch (text[i])
+        {
+            case 'a': case 'e': case 'i':
+                vowels++;
+                break;
+            default:
+                continue;
+        }
+    }
+
+    return vowels;
+}
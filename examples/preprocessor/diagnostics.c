@@ -0,0 +1,18 @@
+// Pragmas, #warning and #line don't stop compilation
+#pragma STDC FP_CONTRACT OFF
+#pragma pack(push, 4)
+#pragma pack(pop)
+
+#define IGNORE_UNKNOWN _Pragma("GCC diagnostic ignored \"-Wunknown-pragmas\"")
+_Pragma("GCC diagnostic push")
+IGNORE_UNKNOWN
+#pragma not_a_real_pragma
+_Pragma("GCC diagnostic pop")
+
+#warning diagnostics.c is an example
+
+#line 1000 "renamed.c"
+int line()
+{
+    return __LINE__;
+}
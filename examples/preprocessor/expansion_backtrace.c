@@ -0,0 +1,8 @@
+// Errors inside macro expansions list the macros they were expanded from
+#define RETURN(value) return value
+#define BROKEN(x) RETURN((x +))
+
+int broken()
+{
+    BROKEN(1);
+}
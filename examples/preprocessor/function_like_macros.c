@@ -0,0 +1,22 @@
+// Function like macros are expanded with their arguments, and their replacements are rescanned
+#define SUM(a, b) ((a) + (b))
+#define SQUARE(x) ((x) * (x))
+#define APPLY(f, x) f(x)
+#define ZERO() 0
+#define SQUARE_OF_SUM SQUARE(SUM
+
+int square(int x)
+{
+    return SQUARE (x) + ZERO();
+}
+
+int sum_square(int x, int y)
+{
+    return APPLY(square, SUM(x,
+                             y));
+}
+
+int square_of_sum(int x, int y)
+{
+    return SQUARE_OF_SUM(x, y));
+}
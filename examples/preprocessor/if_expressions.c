@@ -0,0 +1,23 @@
+// Conditions are evaluated with intmax_t and uintmax_t semantics, and undefined names count as zero
+#define VERSION 3
+#define SCALE(x) ((x) * 4)
+
+#if VERSION >= 3 && defined(SCALE) && !defined UNDEFINED_NAME
+int version()
+{
+    return VERSION;
+}
+#elif 1 / 0
+#error "never evaluated, an earlier group was kept"
+#else
+int version()
+{
+    return 0;
+}
+#endif
+
+#if -1 < 0u
+int signedness() { return 0; }
+#elifdef SCALE
+int signedness() { return SCALE(1); }
+#endif
@@ -0,0 +1,12 @@
+// Include names may come from a macro, and __has_include checks whether a header can be found
+#define HEADER "include_search.h"
+#include HEADER
+
+#if __has_include(<no_such_header.h>)
+#include <no_such_header.h>
+#endif
+
+int value()
+{
+    return header_value();
+}
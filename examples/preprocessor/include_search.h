@@ -0,0 +1,4 @@
+int header_value()
+{
+    return 7;
+}
@@ -0,0 +1,9 @@
+#define LONG_MACRO 1 + \
+    2 + \
+    3
+
+int spliced()
+{
+    return LONG_MACRO + spli\
+ced_identifier;
+}
@@ -0,0 +1,8 @@
+// Headers marked with #pragma once are only included the first time
+#include "pragma_once.h"
+#include "pragma_once.h"
+
+int value()
+{
+    return once_value();
+}
@@ -0,0 +1,6 @@
+#pragma once
+
+int once_value()
+{
+    return 3;
+}
@@ -0,0 +1,23 @@
+// Predefined macros identify the compiler and target, and __LINE__ follows where it is expanded
+#if defined(__qorcc__) && defined(__riscv) && __riscv_xlen == 64
+#define WORD_BYTES 8
+#else
+#define WORD_BYTES 4
+#endif
+
+#define HERE __LINE__
+
+int word_bytes()
+{
+    return WORD_BYTES + __SIZEOF_POINTER__;
+}
+
+int int_max()
+{
+    return __INT_MAX__ - __CHAR_BIT__;
+}
+
+int line()
+{
+    return HERE + __STDC__;
+}
@@ -0,0 +1,18 @@
+/* Run with -E to see the preprocessed source, adding -C to keep comments and -P to leave out line markers */
+#include "include_search.h"
+
+#define TWICE(x) x + x
+#define SUM(a, b) (a + b)
+
+int spread()
+{
+    // Arguments on several lines are written out with the rest of their expansion
+    return SUM(1,
+               2) * SUM(TWICE(3),
+                        4);
+}
+# 100 "generated.c"
+int marked()
+{
+    return __LINE__;
+}
@@ -0,0 +1,61 @@
+// Macro replacements are rescanned, but never replaced by a macro they came from
+int f(int a)
+{
+    return a + 1;
+}
+
+int t(int a)
+{
+    return a;
+}
+
+// Example 3 from the macro replacement section of the C standard
+#define x 3
+#define f(a) f(x * (a))
+#undef x
+#define x 2
+#define g f
+#define h g(1 +
+#define t(a) a
+
+int standard(int y)
+{
+    // f(2 * (y + 1)) + f(2 * (f(2 * (0))))
+    return f(y + 1) + f(f(0));
+}
+
+int unbalanced()
+{
+    // f(2 * (1 + 5))
+    return h 5);
+}
+
+int nested()
+{
+    // f(2 * (0)) + t(1)
+    return t(t(g)(0) + t)(1);
+}
+
+// Object like macros rescan their replacements
+#define A B
+#define B 1
+
+// Self referential macros stop after one replacement
+#define foo foo
+#define aa bb
+#define bb aa
+
+int recursive(int foo, int aa, int bb)
+{
+    return A + foo + aa + bb;
+}
+
+// An invocation finished by tokens after the replacement is hidden only from the macro invoked
+#define ff(a) a * gg
+#define gg(a) ff(a)
+
+int partial(int gg)
+{
+    // 2 * 9 * gg
+    return ff(2)(9);
+}
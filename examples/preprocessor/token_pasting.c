@@ -0,0 +1,15 @@
+// The ## operator joins two tokens into one, which is lexed again as a single token
+#define CAT(a, b) a ## b
+#define REG(name) REG_ ## name ## _OFFSET
+#define REG_UART_OFFSET 16
+#define VERSION 3
+
+int uart_offset()
+{
+    return REG(UART);
+}
+
+int version()
+{
+    return CAT(VER, SION) + CAT(1, 0) + CAT(, 2);
+}
@@ -0,0 +1,20 @@
+// Variadic macros collect their trailing arguments into __VA_ARGS__
+#define CALL(f, ...) f(__VA_ARGS__)
+#define PLUS(x, ...) (x __VA_OPT__(+) __VA_ARGS__)
+#define CALL_GNU(f, x, ...) f(x, ## __VA_ARGS__)
+#define FIRST(x, ...) x
+
+int identity(int a)
+{
+    return a;
+}
+
+int sum(int x, int y)
+{
+    return CALL(identity, x) + PLUS(x) + PLUS(x, y + 1);
+}
+
+int first(int x, int y)
+{
+    return CALL_GNU(identity, FIRST(x, y));
+}
@@ -0,0 +1,6 @@
+%:define DIGRAPH_MACRO 7
+
+int digraphs(int* a)
+<%
+    return a<:DIGRAPH_MACRO:>;
+%>
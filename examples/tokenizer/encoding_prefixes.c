@@ -0,0 +1,5 @@
+// Literals with encoding prefixes, universal character names and Unicode identifiers
+int größe(int café)
+{
+    return café + L'€' + u'\u00e9' + U'\U0001F600' + u8'a' + 'é' + caf\u00e9;
+}
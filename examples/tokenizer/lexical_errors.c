@@ -0,0 +1,9 @@
+// Every lexical error which survives preprocessing is reported, not just the first
+int main()
+{
+    int a = 0x;
+    int b = 1 @ 2;
+    char c = '\x100';
+    int $d = 3;
+    return a + b;
+}
@@ -0,0 +1,19 @@
+int integers()
+{
+    return 0x7f + 0X10 + 017 + 0b1011 + 1'000'000 + 0xFFFF'FFFF + 0;
+}
+
+long suffixes()
+{
+    return 42u + 42U + 42l + 42L + 42ul + 42LU + 42ll + 42LL + 42ull + 42LLU;
+}
+
+long widths()
+{
+    return 2147483647 + 2147483648 + 0x80000000 + 0xFFFFFFFF + 0x100000000 + 9223372036854775807 + 0xFFFFFFFFFFFFFFFF;
+}
+
+int floating()
+{
+    return 1.5 + .5 + 1. + 1e10 + 1.5e-3f + 2.0L + 0x1p4 + 0x1.8p-1 + 0X.8P+1f;
+}
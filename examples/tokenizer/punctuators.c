@@ -0,0 +1,18 @@
+#define STRINGIFY(x) #x
+#define PASTE(a, b) a ## b
+
+int operators(int a, int b, int* p)
+{
+    a[b]; p->f; a.f; a++; a--; ++a; --a;
+    &a; *p; +a; -a; ~a; !a;
+    a * b; a / b; a % b; a + b; a - b;
+    a << b; a >> b;
+    a < b; a > b; a <= b; a >= b; a == b; a != b;
+    a & b; a ^ b; a | b; a && b; a || b;
+    a ? b : a;
+    a = b; a *= b; a /= b; a %= b; a += b; a -= b;
+    a <<= b; a >>= b; a &= b; a ^= b; a |= b;
+    a, b;
+    a+++b; a---b; a<<=b; a->b; a...b;
+    return 0;
+}
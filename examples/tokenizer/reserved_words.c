@@ -0,0 +1,5 @@
+// Reserved words can't be used as the names of functions or variables
+int while(int a)
+{
+    return a;
+}
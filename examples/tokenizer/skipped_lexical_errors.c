@@ -0,0 +1,10 @@
+// Text which can't be lexed is only an error if it survives preprocessing, so it can appear in skipped groups and in
+// diagnostic directives
+#if 0
+don't use $ in identifiers or 08 as a number
+#endif
+#warning don't worry about $ or 08 here
+int main()
+{
+    return 0;
+}
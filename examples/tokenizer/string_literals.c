@@ -0,0 +1,14 @@
+#include "path with spaces.h"
+
+int characters()
+{
+    return 'x' + '\n' + '\t' + '\\' + '\'' + '\0' + '\101' + '\x7f';
+}
+
+int strings()
+{
+    "a b";
+    "escapes \" \\ \n \t \123 \x7f";
+    "adjacent " "strings " "are concatenated";
+    return 0;
+}
@@ -0,0 +1,6 @@
+??=define TRIGRAPH_MACRO 7
+
+int trigraphs(int* a)
+??<
+    return a??(TRIGRAPH_MACRO??) ??! ??-a[0];
+??>
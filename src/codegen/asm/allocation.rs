@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use crate::codegen::{IRFunction, IRInstruction, IRValue};
+use crate::errors::*;
+
+use super::*;
+
+/// Registers which can hold any value, as nothing else writes to them
+const TEMPORARY_REGISTERS: [Register; 6] = [Register::T0, Register::T1, Register::T2, Register::T3, Register::T4, Register::T5];
+
+/// Registers arguments are passed in, in order
+const ARGUMENT_REGISTERS: [Register; 8] = [Register::A0, Register::A1, Register::A2, Register::A3, Register::A4, Register::A5, Register::A6, Register::A7];
+
+/// Get the IR registers an instruction reads and those it writes
+fn instruction_registers(instruction: &IRInstruction) -> (Vec<&IRValue>, Vec<&IRValue>)
+{
+    match instruction
+    {
+        IRInstruction::Return { value } => (vec![value], vec![]),
+        IRInstruction::Add { dest, src1, src2 } | IRInstruction::Sub { dest, src1, src2 } |
+        IRInstruction::Mul { dest, src1, src2 } | IRInstruction::Div { dest, src1, src2 } |
+        IRInstruction::Mod { dest, src1, src2 } | IRInstruction::And { dest, src1, src2 } |
+        IRInstruction::Or { dest, src1, src2 } | IRInstruction::Xor { dest, src1, src2 } |
+        IRInstruction::ShiftLeft { dest, src1, src2 } | IRInstruction::ShiftRight { dest, src1, src2, .. } |
+        IRInstruction::Conditional { dest, src1, src2, .. } => (vec![src1, src2], vec![dest]),
+        IRInstruction::Load { dest, address, .. } => (vec![address], vec![dest]),
+        IRInstruction::Store { src, address, .. } => (vec![src, address], vec![]),
+        IRInstruction::Branch { src1, src2, .. } => (vec![src1, src2], vec![]),
+        IRInstruction::JumpTable { offset, .. } => (vec![offset], vec![]),
+        IRInstruction::IndirectJump { address, .. } => (vec![address], vec![]),
+        IRInstruction::LoadLabel { dest, .. } => (vec![], vec![dest]),
+        IRInstruction::FunctionCall { arguments, .. } => (arguments.iter().collect(), vec![]),
+        IRInstruction::LoadRet { dest } => (vec![], vec![dest]),
+        IRInstruction::Spill { src, .. } => (vec![src], vec![]),
+        IRInstruction::Reload { dest, .. } => (vec![], vec![dest]),
+        IRInstruction::Jump { .. } | IRInstruction::Backup { .. } | IRInstruction::Restore { .. } => (vec![], vec![])
+    }
+}
+
+/// Get every value an instruction reads or writes, so IR registers can be renamed
+fn instruction_values_mut(instruction: &mut IRInstruction) -> Vec<&mut IRValue>
+{
+    match instruction
+    {
+        IRInstruction::Return { value } => vec![value],
+        IRInstruction::Add { dest, src1, src2 } | IRInstruction::Sub { dest, src1, src2 } |
+        IRInstruction::Mul { dest, src1, src2 } | IRInstruction::Div { dest, src1, src2 } |
+        IRInstruction::Mod { dest, src1, src2 } | IRInstruction::And { dest, src1, src2 } |
+        IRInstruction::Or { dest, src1, src2 } | IRInstruction::Xor { dest, src1, src2 } |
+        IRInstruction::ShiftLeft { dest, src1, src2 } | IRInstruction::ShiftRight { dest, src1, src2, .. } |
+        IRInstruction::Conditional { dest, src1, src2, .. } => vec![dest, src1, src2],
+        IRInstruction::Load { dest, address, .. } => vec![dest, address],
+        IRInstruction::Store { src, address, .. } => vec![src, address],
+        IRInstruction::Branch { src1, src2, .. } => vec![src1, src2],
+        IRInstruction::JumpTable { offset, .. } => vec![offset],
+        IRInstruction::IndirectJump { address, .. } => vec![address],
+        IRInstruction::LoadLabel { dest, .. } | IRInstruction::LoadRet { dest } | IRInstruction::Reload { dest, .. } => vec![dest],
+        IRInstruction::Spill { src, .. } => vec![src],
+        IRInstruction::FunctionCall { arguments, .. } => arguments.iter_mut().collect(),
+        IRInstruction::Jump { .. } | IRInstruction::Backup { .. } | IRInstruction::Restore { .. } => vec![]
+    }
+}
+
+/// Blocks an instruction may jump to
+fn jump_targets(instruction: &IRInstruction) -> Vec<usize>
+{
+    match instruction
+    {
+        IRInstruction::Jump { dest } => vec![*dest],
+        IRInstruction::Branch { dest_true, dest_false, .. } => vec![*dest_true, *dest_false],
+        IRInstruction::JumpTable { targets, .. } | IRInstruction::IndirectJump { targets, .. } => targets.clone(),
+        _ => vec![]
+    }
+}
+
+/// Find the range of instructions, numbered in the order the blocks are laid out, over which each IR register holds a
+/// value which may still be read
+fn live_intervals(function: &IRFunction) -> HashMap<usize, (usize, usize)>
+{
+    let mut intervals: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut block_starts = Vec::new();
+    let mut back_edges = Vec::new();
+    let mut position = 0;
+
+    for block in &function.blocks
+    {
+        block_starts.push(position);
+
+        for instruction in &block.instructions
+        {
+            let (reads, writes) = instruction_registers(instruction);
+
+            for value in reads
+            {
+                if let IRValue::Register(register) = value
+                {
+                    // A register read before it is first written holds a value from the start of the function
+                    intervals.entry(*register).or_insert((0, position)).1 = position;
+                }
+            }
+
+            for value in writes
+            {
+                if let IRValue::Register(register) = value
+                {
+                    intervals.entry(*register).or_insert((position, position)).1 = position;
+                }
+            }
+
+            back_edges.extend(jump_targets(instruction).into_iter().map(|target| (target, position)));
+
+            position += 1;
+        }
+    }
+
+    // Values which are live when a loop starts must survive every iteration, so they are kept until the jump back
+    let back_edges = back_edges.into_iter().map(|(target, source)| (block_starts[target], source)).filter(|(target, source)| target <= source).collect::<Vec<_>>();
+    let mut changed = true;
+
+    while changed
+    {
+        changed = false;
+
+        for (start, end) in intervals.values_mut()
+        {
+            for (loop_start, loop_end) in &back_edges
+            {
+                if *start < *loop_start && *end >= *loop_start && *end < *loop_end
+                {
+                    *end = *loop_end;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    intervals
+}
+
+/// Back up every IR register whose value is still needed after a call, restoring it once the return value is read
+///
+/// Every register the allocator hands out is caller saved, so the called function is free to overwrite any of them.
+pub fn preserve_across_calls(function: &mut IRFunction)
+{
+    let intervals = live_intervals(function);
+    let mut position = 0;
+
+    for block in &mut function.blocks
+    {
+        let mut instructions = Vec::new();
+        let mut restores = Vec::new();
+
+        for instruction in block.instructions.drain(..)
+        {
+            let is_call = matches!(instruction, IRInstruction::FunctionCall { .. });
+            let is_load_ret = matches!(instruction, IRInstruction::LoadRet { .. });
+
+            if is_call
+            {
+                // Values written before the call and read after it are live across it
+                let mut live = intervals.iter().filter(|(_, (start, end))| *start < position && *end > position).map(|(register, _)| *register).collect::<Vec<_>>();
+                live.sort_unstable();
+
+                instructions.extend(live.iter().map(|register| IRInstruction::Backup { register: *register }));
+                restores.extend(live.into_iter().rev().map(|register| IRInstruction::Restore { register }));
+            }
+
+            instructions.push(instruction);
+
+            // The return value is moved out of a0 before anything which was in a0 is restored
+            if is_load_ret
+            {
+                instructions.append(&mut restores);
+            }
+
+            position += 1;
+        }
+
+        block.instructions = instructions;
+    }
+}
+
+/// Outcome of assigning physical registers
+enum Assignment
+{
+    /// Every IR register was given a physical register
+    Complete(HashMap<usize, Register>),
+    /// The physical registers ran out, and the IR register given should be kept on the stack instead
+    Spill(usize)
+}
+
+/// Assign a physical register to every IR register of a function, letting registers whose values are no longer needed
+/// be reused
+///
+/// Arguments stay in the registers they are passed in. Argument registers which neither the function's arguments nor
+/// the calls it makes use are also handed out. Registers from `first_unspillable` onward only hold values between a
+/// reload or spill and the instruction next to it, so they are never chosen to be spilled.
+fn assign_registers(function: &IRFunction, first_unspillable: usize) -> CompilerResult<Assignment>
+{
+    let mut mapping = HashMap::new();
+
+    if function.arguments > ARGUMENT_REGISTERS.len()
+    {
+        return Err(CompilerError::OutOfRegisters(function.name.clone()));
+    }
+
+    for (register, physical) in ARGUMENT_REGISTERS.iter().take(function.arguments).enumerate()
+    {
+        mapping.insert(register, *physical);
+    }
+
+    // Calls write their arguments and return value to the first argument registers
+    let call_arguments = function.blocks.iter().flat_map(|block| block.instructions.iter()).filter_map(|instruction| match instruction
+    {
+        IRInstruction::FunctionCall { arguments, .. } => Some(arguments.len()),
+        _ => None
+    }).max();
+
+    let reserved = function.arguments.max(call_arguments.unwrap_or(0)).max(1);
+    let mut free = TEMPORARY_REGISTERS.iter().chain(ARGUMENT_REGISTERS.iter().skip(reserved)).copied().collect::<Vec<_>>();
+
+    let mut intervals = live_intervals(function).into_iter().filter(|(register, _)| *register >= function.arguments).collect::<Vec<_>>();
+    intervals.sort_by_key(|(register, (start, _))| (*start, *register));
+
+    let mut active: Vec<(usize, usize, Register)> = Vec::new();
+
+    for (register, (start, end)) in intervals
+    {
+        // Registers whose values were last read before this one is written can be used again
+        active.retain(|(active_end, _, physical)|
+        {
+            let expired = *active_end < start;
+
+            if expired
+            {
+                free.push(*physical);
+            }
+
+            !expired
+        });
+
+        if free.is_empty()
+        {
+            // The value which is next needed furthest away is the one that is cheapest to keep on the stack
+            let furthest = active.iter().map(|(end, register, _)| (*end, *register)).chain(std::iter::once((end, register)))
+                .filter(|(_, register)| *register < first_unspillable)
+                .max();
+
+            return match furthest
+            {
+                Some((_, register)) => Ok(Assignment::Spill(register)),
+                None => Err(CompilerError::OutOfRegisters(function.name.clone()))
+            };
+        }
+
+        let physical = free.remove(0);
+
+        active.push((end, register, physical));
+        mapping.insert(register, physical);
+    }
+
+    Ok(Assignment::Complete(mapping))
+}
+
+/// Keep an IR register in a stack slot, reloading it into a new register before each instruction which reads it and
+/// spilling the new register written by each instruction which writes it
+fn spill_register(function: &mut IRFunction, register: usize, slot: usize)
+{
+    let spilled = IRValue::Register(register);
+    let mut next_register = function.next_register;
+
+    for block in &mut function.blocks
+    {
+        let mut instructions = Vec::new();
+
+        for mut instruction in block.instructions.drain(..)
+        {
+            let (reads, writes) = instruction_registers(&instruction);
+            let (reads, writes) = (reads.contains(&&spilled), writes.contains(&&spilled));
+
+            if !reads && !writes
+            {
+                instructions.push(instruction);
+                continue;
+            }
+
+            let temporary = IRValue::Register(next_register);
+            next_register += 1;
+
+            for value in instruction_values_mut(&mut instruction)
+            {
+                if *value == spilled
+                {
+                    *value = temporary.clone();
+                }
+            }
+
+            if reads
+            {
+                instructions.push(IRInstruction::Reload { dest: temporary.clone(), slot });
+            }
+
+            instructions.push(instruction);
+
+            if writes
+            {
+                instructions.push(IRInstruction::Spill { src: temporary, slot });
+            }
+        }
+
+        block.instructions = instructions;
+    }
+
+    function.next_register = next_register;
+}
+
+/// Spill IR registers to the stack until the rest fit in the physical registers
+pub fn spill_registers(function: &mut IRFunction) -> CompilerResult<()>
+{
+    let first_unspillable = function.next_register;
+    let mut slot = 0;
+
+    while let Assignment::Spill(register) = assign_registers(function, first_unspillable)?
+    {
+        spill_register(function, register, slot);
+        slot += 1;
+    }
+
+    Ok(())
+}
+
+/// Assign a physical register to every IR register of a function, which must already have had enough of its registers
+/// spilled for the rest to fit
+pub fn allocate_registers(function: &IRFunction) -> CompilerResult<HashMap<usize, Register>>
+{
+    match assign_registers(function, function.next_register)?
+    {
+        Assignment::Complete(mapping) => Ok(mapping),
+        Assignment::Spill(_) => Err(CompilerError::OutOfRegisters(function.name.clone()))
+    }
+}
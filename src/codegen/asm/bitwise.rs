@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+use crate::codegen::*;
+use super::*;
+
+impl AssemblyCodeGenerator
+{
+    /// Emit a bitwise operation, given the mnemonic of its register form, which with an i suffix is its immediate form
+    fn bitwise_reg_value_value(&self, mnemonic: &str, fold: fn(u64, u64) -> u64, dest: Register, src1: &IRValue, src2: &IRValue) -> String
+    {
+        // The operations are commutative, so an immediate operand can always go second
+        match (src1, src2)
+        {
+            (IRValue::Immediate(imm0), IRValue::Immediate(imm1)) => self.move_reg_imm(dest, fold(imm0.value, imm1.value) as i64),
+            (IRValue::Immediate(imm), IRValue::Register(reg)) | (IRValue::Register(reg), IRValue::Immediate(imm)) =>
+            {
+                let imm = imm.value as i64;
+
+                // The immediate forms only take a 12 bit signed immediate, so larger constants are loaded first
+                if (-2048..=2047).contains(&imm)
+                {
+                    format!("    {}i {}, {}, {}\n", mnemonic, dest, self.mapping.get(reg).unwrap(), imm)
+                }
+                else
+                {
+                    format!("    li t6, {}\n    {} {}, {}, t6\n", imm, mnemonic, dest, self.mapping.get(reg).unwrap())
+                }
+            },
+            (IRValue::Register(reg0), IRValue::Register(reg1)) =>
+            {
+                format!("    {} {}, {}, {}\n", mnemonic, dest, self.mapping.get(reg0).unwrap(), self.mapping.get(reg1).unwrap())
+            }
+        }
+    }
+
+    pub fn and_reg_value_value(&self, dest: Register, src1: &IRValue, src2: &IRValue) -> String
+    {
+        self.bitwise_reg_value_value("and", |a, b| a & b, dest, src1, src2)
+    }
+
+    pub fn or_reg_value_value(&self, dest: Register, src1: &IRValue, src2: &IRValue) -> String
+    {
+        self.bitwise_reg_value_value("or", |a, b| a | b, dest, src1, src2)
+    }
+
+    pub fn xor_reg_value_value(&self, dest: Register, src1: &IRValue, src2: &IRValue) -> String
+    {
+        self.bitwise_reg_value_value("xor", |a, b| a ^ b, dest, src1, src2)
+    }
+}
@@ -11,51 +11,45 @@ use crate::errors::*;
 pub struct AssemblyCodeGenerator
 {
     pub ir: IR,
-    pub mapping: HashMap<usize, Register>
+    pub mapping: HashMap<usize, Register>,
+    /// Stack frame of the function being generated
+    pub frame: StackFrame
 }
 
 impl AssemblyCodeGenerator
 {
     pub fn from_ir(ir: IR) -> Self
     {
-        let mut default_mapping = HashMap::new();
-
-        default_mapping.insert(0, Register::A0);
-        default_mapping.insert(1, Register::A1);
-        default_mapping.insert(2, Register::A2);
-        default_mapping.insert(3, Register::A3);
-        default_mapping.insert(4, Register::A4);
-        default_mapping.insert(5, Register::A5);
-        default_mapping.insert(6, Register::A6);
-        default_mapping.insert(7, Register::A7);
-        default_mapping.insert(8, Register::T0);
-        default_mapping.insert(9, Register::T1);
-        default_mapping.insert(10, Register::T2);
-        default_mapping.insert(11, Register::T3);
-        default_mapping.insert(12, Register::T4);
-        default_mapping.insert(13, Register::T5);
-
         Self
         {
             ir,
-            mapping: default_mapping
+            mapping: HashMap::new(),
+            frame: StackFrame::default()
         }
     }
 
-    pub fn codegen(&self) -> CompilerResult<String>
+    pub fn codegen(&mut self) -> CompilerResult<String>
     {
         trace!("Generating Assembly");
 
         let mut result = String::new();
 
-        for function in &self.ir.functions
+        for function in &self.ir.functions.clone()
         {
             if result.len() > 0
             {  
                 result += "\n";
             }
+
+            let mut function = function.clone();
+            spill_registers(&mut function)?;
+            preserve_across_calls(&mut function);
+
+            // Each function has its own assignment of physical registers
+            self.mapping = allocate_registers(&function)?;
+            self.frame = StackFrame::new(&function);
             
-            result += &self.emit_function(function)?;
+            result += &self.emit_function(&function)?;
         }
 
         Ok(result)
@@ -68,6 +62,7 @@ impl AssemblyCodeGenerator
         let mut result = String::new();
 
         result += &format!(".globl {}\n{}:\n", name, name);
+        result += &self.add_prologue();
 
         for block in &function.blocks
         {
@@ -101,13 +96,13 @@ impl AssemblyCodeGenerator
         self.block_label_raw(&function.blocks[block], function)
     }
 
-    pub fn emit_instruction(&self, inst: &IRInstruction, _block: &IRBlock, function: &IRFunction) -> CompilerResult<String>
+    pub fn emit_instruction(&self, inst: &IRInstruction, block: &IRBlock, function: &IRFunction) -> CompilerResult<String>
     {
         match inst
         {
             IRInstruction::Return { value } => 
             {
-                Ok(self.move_reg_value(Register::A0, value.clone()) + "    ret\n")
+                Ok(self.move_reg_value(Register::A0, value.clone()) + &self.add_epilogue() + "    ret\n")
             },
             IRInstruction::Add { dest, src1, src2  } => 
             {
@@ -164,10 +159,84 @@ impl AssemblyCodeGenerator
                     unreachable!()
                 }
             },
+            IRInstruction::And { dest, src1, src2 } | IRInstruction::Or { dest, src1, src2 } | IRInstruction::Xor { dest, src1, src2 } =>
+            {
+                if let IRValue::Register(dest) = dest
+                {
+                    let dest = *self.mapping.get(dest).unwrap();
+
+                    Ok(match inst
+                    {
+                        IRInstruction::And { .. } => self.and_reg_value_value(dest, src1, src2),
+                        IRInstruction::Or { .. } => self.or_reg_value_value(dest, src1, src2),
+                        _ => self.xor_reg_value_value(dest, src1, src2)
+                    })
+                }
+                else
+                {
+                    unreachable!()
+                }
+            },
+            IRInstruction::ShiftLeft { dest, src1, src2 } =>
+            {
+                if let IRValue::Register(dest) = dest
+                {
+                    Ok(self.shift_reg_value_value(*self.mapping.get(dest).unwrap(), src1, src2, true, false))
+                }
+                else
+                {
+                    unreachable!()
+                }
+            },
+            IRInstruction::ShiftRight { dest, src1, src2, arithmetic } =>
+            {
+                if let IRValue::Register(dest) = dest
+                {
+                    Ok(self.shift_reg_value_value(*self.mapping.get(dest).unwrap(), src1, src2, false, *arithmetic))
+                }
+                else
+                {
+                    unreachable!()
+                }
+            },
+            IRInstruction::Load { dest, address, size, signed } =>
+            {
+                if let IRValue::Register(dest) = dest
+                {
+                    Ok(self.load_reg_value(*self.mapping.get(dest).unwrap(), address, *size, *signed, function.pointer_size))
+                }
+                else
+                {
+                    unreachable!()
+                }
+            },
+            IRInstruction::Store { src, address, size } =>
+            {
+                Ok(self.store_value_value(src, address, *size))
+            },
             IRInstruction::Jump { dest } =>
             {
                 Ok(self.add_jump(*dest, function))
             },
+            IRInstruction::JumpTable { offset, targets } =>
+            {
+                Ok(self.add_jump_table(offset, targets, block, function))
+            },
+            IRInstruction::IndirectJump { address, .. } =>
+            {
+                Ok(self.add_indirect_jump(address))
+            },
+            IRInstruction::LoadLabel { dest, block } =>
+            {
+                if let IRValue::Register(dest) = dest
+                {
+                    Ok(self.load_label_address(*self.mapping.get(dest).unwrap(), *block, function))
+                }
+                else
+                {
+                    unreachable!()
+                }
+            },
             IRInstruction::Branch { condition, src1, src2, dest_true, dest_false } =>
             {
                 Ok(self.add_branch(*condition, src1.clone(), src2.clone(), *dest_true, *dest_false, function))
@@ -193,11 +262,19 @@ impl AssemblyCodeGenerator
             },
             IRInstruction::Backup { register } =>
             {
-                Ok(format!("    push {}\n", self.mapping.get(register).unwrap()))
+                Ok(self.add_backup(*register))
             },
             IRInstruction::Restore { register } =>
             {
-                Ok(format!("    pop {}\n", self.mapping.get(register).unwrap()))
+                Ok(self.add_restore(*register))
+            },
+            IRInstruction::Spill { src, slot } =>
+            {
+                Ok(self.add_spill(src, *slot))
+            },
+            IRInstruction::Reload { dest, slot } =>
+            {
+                Ok(self.add_reload(dest, *slot))
             },
             IRInstruction::FunctionCall { name, arguments } =>
             {
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::codegen::{IRFunction, IRInstruction, IRValue};
+
+use super::*;
+
+/// Layout of a function's stack frame
+///
+/// The return address is saved at the top of the frame, and below it each register backed up across calls has a slot
+/// of its own. The slots of spilled values are at the bottom.
+#[derive(Debug, Clone, Default)]
+pub struct StackFrame
+{
+    /// Size of the frame in bytes, kept a multiple of 16 as the calling convention requires
+    pub size: usize,
+    /// Size of a register on the target, in bytes
+    pub word: usize,
+    /// True if the function calls others, which overwrite the return address
+    pub saves_return_address: bool,
+    /// Offset from the stack pointer of the slot each backed up IR register is saved to
+    pub slots: HashMap<usize, usize>,
+    /// Offset from the stack pointer of the first spill slot
+    pub spill_offset: usize
+}
+
+impl StackFrame
+{
+    pub fn new(function: &IRFunction) -> Self
+    {
+        let word = function.pointer_size;
+        let mut saves_return_address = false;
+        let mut slots = HashMap::new();
+        let mut spills = 0;
+
+        for instruction in function.blocks.iter().flat_map(|block| block.instructions.iter())
+        {
+            match instruction
+            {
+                IRInstruction::FunctionCall { .. } => saves_return_address = true,
+                IRInstruction::Backup { register } =>
+                {
+                    let offset = slots.len() * word;
+                    slots.entry(*register).or_insert(offset);
+                },
+                IRInstruction::Spill { slot, .. } | IRInstruction::Reload { slot, .. } => spills = spills.max(*slot + 1),
+                _ => {}
+            }
+        }
+
+        let spill_offset = slots.len() * word;
+        let used = spill_offset + (spills + saves_return_address as usize) * word;
+
+        Self
+        {
+            size: used.next_multiple_of(16),
+            word,
+            saves_return_address,
+            slots,
+            spill_offset
+        }
+    }
+}
+
+impl AssemblyCodeGenerator
+{
+    /// Store a whole register to the stack frame
+    fn store_reg_frame(&self, src: Register, offset: usize) -> String
+    {
+        let mnemonic = if self.frame.word > 4 { "sd" } else { "sw" };
+
+        format!("    {} {}, {}(sp)\n", mnemonic, src, offset)
+    }
+
+    /// Load a whole register from the stack frame
+    fn load_reg_frame(&self, dest: Register, offset: usize) -> String
+    {
+        let mnemonic = if self.frame.word > 4 { "ld" } else { "lw" };
+
+        format!("    {} {}, {}(sp)\n", mnemonic, dest, offset)
+    }
+
+    /// Make room for the stack frame on entry to a function, saving the return address if it calls others
+    pub fn add_prologue(&self) -> String
+    {
+        if self.frame.size == 0
+        {
+            return String::new();
+        }
+
+        let mut result = self.add_reg_imm(Register::Sp, Register::Sp, -(self.frame.size as i64));
+
+        if self.frame.saves_return_address
+        {
+            result += &self.store_reg_frame(Register::Ra, self.frame.size - self.frame.word);
+        }
+
+        result
+    }
+
+    /// Undo the prologue before returning
+    pub fn add_epilogue(&self) -> String
+    {
+        if self.frame.size == 0
+        {
+            return String::new();
+        }
+
+        let mut result = String::new();
+
+        if self.frame.saves_return_address
+        {
+            result += &self.load_reg_frame(Register::Ra, self.frame.size - self.frame.word);
+        }
+
+        result + &self.add_reg_imm(Register::Sp, Register::Sp, self.frame.size as i64)
+    }
+
+    /// Save the physical register holding an IR register to its slot
+    pub fn add_backup(&self, register: usize) -> String
+    {
+        self.store_reg_frame(*self.mapping.get(&register).unwrap(), *self.frame.slots.get(&register).unwrap())
+    }
+
+    /// Load an IR register back from its slot
+    pub fn add_restore(&self, register: usize) -> String
+    {
+        self.load_reg_frame(*self.mapping.get(&register).unwrap(), *self.frame.slots.get(&register).unwrap())
+    }
+
+    /// Store a value to a spill slot
+    pub fn add_spill(&self, src: &IRValue, slot: usize) -> String
+    {
+        let offset = self.frame.spill_offset + slot * self.frame.word;
+
+        match src
+        {
+            IRValue::Register(reg) => self.store_reg_frame(*self.mapping.get(reg).unwrap(), offset),
+            IRValue::Immediate(_) => unreachable!("Only registers are spilled")
+        }
+    }
+
+    /// Load a value back from a spill slot
+    pub fn add_reload(&self, dest: &IRValue, slot: usize) -> String
+    {
+        let offset = self.frame.spill_offset + slot * self.frame.word;
+
+        match dest
+        {
+            IRValue::Register(reg) => self.load_reg_frame(*self.mapping.get(reg).unwrap(), offset),
+            IRValue::Immediate(_) => unreachable!()
+        }
+    }
+}
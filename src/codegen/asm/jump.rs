@@ -8,4 +8,45 @@ impl AssemblyCodeGenerator
     {
         format!("    j {}\n", self.block_label(branch_index, function))
     }
+
+    /// Jump to the block whose address is at an offset into a table, which is placed in .rodata after the jump
+    pub fn add_jump_table(&self, offset: &IRValue, targets: &[usize], block: &IRBlock, function: &IRFunction) -> String
+    {
+        let table = format!("{}_table", self.block_label_raw(block, function));
+
+        let (load, directive, alignment) = if function.pointer_size > 4 { ("ld", ".dword", 3) } else { ("lw", ".word", 2) };
+
+        let offset = match offset
+        {
+            IRValue::Register(reg) => self.mapping.get(reg).unwrap(),
+            IRValue::Immediate(_) => unreachable!("Jump table offsets are always held in registers")
+        };
+
+        let mut result = format!("    la t6, {}\n    add t6, t6, {}\n    {} t6, 0(t6)\n    jr t6\n", table, offset, load);
+
+        result += &format!("    .section .rodata\n    .p2align {}\n  {}:\n", alignment, table);
+
+        for target in targets
+        {
+            result += &format!("    {} {}\n", directive, self.block_label(*target, function));
+        }
+
+        result + "    .text\n"
+    }
+
+    /// Jump to the address held in a register
+    pub fn add_indirect_jump(&self, address: &IRValue) -> String
+    {
+        match address
+        {
+            IRValue::Register(reg) => format!("    jr {}\n", self.mapping.get(reg).unwrap()),
+            IRValue::Immediate(_) => unreachable!("Indirect jump addresses are always held in registers")
+        }
+    }
+
+    /// Load the address of a block into a register
+    pub fn load_label_address(&self, dest: Register, block: usize, function: &IRFunction) -> String
+    {
+        format!("    la {}, {}\n", dest, self.block_label(block, function))
+    }
 }
\ No newline at end of file
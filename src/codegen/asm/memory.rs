@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+use crate::codegen::*;
+use super::*;
+
+impl AssemblyCodeGenerator
+{
+    /// Load a value from memory, where a word is the widest load on a 32 bit target and needs no zero extension
+    pub fn load_reg_value(&self, dest: Register, address: &IRValue, size: usize, signed: bool, pointer_size: usize) -> String
+    {
+        let mnemonic = match (size, signed)
+        {
+            (1, true) => "lb",
+            (1, false) => "lbu",
+            (2, true) => "lh",
+            (2, false) => "lhu",
+            (4, false) if pointer_size > 4 => "lwu",
+            (4, _) => "lw",
+            _ => "ld"
+        };
+
+        match address
+        {
+            IRValue::Register(reg) => format!("    {} {}, 0({})\n", mnemonic, dest, self.mapping.get(reg).unwrap()),
+            IRValue::Immediate(_) => unreachable!("Addresses are always held in registers")
+        }
+    }
+
+    /// Store the low bytes of a value to memory
+    pub fn store_value_value(&self, src: &IRValue, address: &IRValue, size: usize) -> String
+    {
+        let mnemonic = match size
+        {
+            1 => "sb",
+            2 => "sh",
+            4 => "sw",
+            _ => "sd"
+        };
+
+        let address = match address
+        {
+            IRValue::Register(reg) => *self.mapping.get(reg).unwrap(),
+            IRValue::Immediate(_) => unreachable!("Addresses are always held in registers")
+        };
+
+        match src
+        {
+            IRValue::Register(reg) => format!("    {} {}, 0({})\n", mnemonic, self.mapping.get(reg).unwrap(), address),
+            IRValue::Immediate(imm) => format!("    li t6, {}\n    {} t6, 0({})\n", imm.value as i64, mnemonic, address)
+        }
+    }
+}
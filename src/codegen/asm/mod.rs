@@ -1,6 +1,11 @@
 pub mod add;
 pub use add::*;
 
+pub mod allocation;
+pub use allocation::*;
+
+pub mod bitwise;
+
 pub mod branch;
 pub use branch::*;
 
@@ -16,12 +21,17 @@ pub use div::*;
 pub mod equality;
 pub use equality::*;
 
+pub mod frame;
+pub use frame::*;
+
 pub mod inequality;
 pub use inequality::*;
 
 pub mod jump;
 pub use jump::*;
 
+pub mod memory;
+
 pub mod modulus;
 pub use modulus::*;
 
@@ -34,5 +44,7 @@ pub use mul::*;
 pub mod registers;
 pub use registers::*;
 
+pub mod shift;
+
 pub mod sub;
 pub use sub::*;
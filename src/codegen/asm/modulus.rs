@@ -11,7 +11,7 @@ impl AssemblyCodeGenerator
 
     pub fn mod_imm_reg(&self, dest: Register, src1: i64, src2: Register) -> String
     {
-        format!("    li t6, {}\n    div {}, t6, {}\n", src1, dest, src2)
+        format!("    li t6, {}\n    rem {}, t6, {}\n", src1, dest, src2)
     }
 
     pub fn mod_reg_reg(&self, dest: Register, src1: Register, src2: Register) -> String
@@ -23,15 +23,16 @@ impl AssemblyCodeGenerator
     {
         if let (IRValue::Immediate(imm0), IRValue::Immediate(imm1)) = (src1, src2)
         {
-            self.move_reg_imm(dest, imm0.value.wrapping_sub(imm1.value) as i64)
+            // The remainder of division by zero is the dividend, as the rem instruction gives
+            self.move_reg_imm(dest, (imm0.value as i64).checked_rem(imm1.value as i64).unwrap_or(imm0.value as i64))
         }
         else if let (IRValue::Immediate(imm), IRValue::Register(reg)) = (src1, src2)
         {
-            self.mod_reg_imm(dest, *self.mapping.get(&reg).unwrap(), imm.value as i64)
+            self.mod_imm_reg(dest, imm.value as i64, *self.mapping.get(&reg).unwrap())
         }
         else if let (IRValue::Register(reg), IRValue::Immediate(imm)) = (src1, src2)
         {
-            self.mod_imm_reg(dest, imm.value as i64, *self.mapping.get(&reg).unwrap())
+            self.mod_reg_imm(dest, *self.mapping.get(&reg).unwrap(), imm.value as i64)
         }
         else if let (IRValue::Register(reg0), IRValue::Register(reg1)) = (src1, src2)
         {
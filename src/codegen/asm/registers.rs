@@ -4,6 +4,10 @@
 pub enum Register
 {
     Zero,
+    /// Return address
+    Ra,
+    /// Stack pointer
+    Sp,
     A0,
     A1,
     A2, 
@@ -27,6 +31,8 @@ impl std::fmt::Display for Register
         match self
         {
             Register::Zero => write!(f, "zero"),
+            Register::Ra => write!(f, "ra"),
+            Register::Sp => write!(f, "sp"),
             Register::A0 => write!(f, "a0"),
             Register::A1 => write!(f, "a1"),
             Register::A2 => write!(f, "a2"),
@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+use crate::codegen::*;
+use super::*;
+
+impl AssemblyCodeGenerator
+{
+    /// Mnemonic of a shift's register form, which with an i suffix is its immediate form
+    fn shift_mnemonic(left: bool, arithmetic: bool) -> &'static str
+    {
+        match (left, arithmetic)
+        {
+            (true, _) => "sll",
+            (false, true) => "sra",
+            (false, false) => "srl"
+        }
+    }
+
+    pub fn shift_reg_value_value(&self, dest: Register, src1: &IRValue, src2: &IRValue, left: bool, arithmetic: bool) -> String
+    {
+        let mnemonic = Self::shift_mnemonic(left, arithmetic);
+
+        match (src1, src2)
+        {
+            (IRValue::Immediate(imm0), IRValue::Immediate(imm1)) =>
+            {
+                let amount = (imm1.value & 63) as u32;
+
+                let value = match (left, arithmetic)
+                {
+                    (true, _) => imm0.value << amount,
+                    (false, true) => ((imm0.value as i64) >> amount) as u64,
+                    (false, false) => imm0.value >> amount
+                };
+
+                self.move_reg_imm(dest, value as i64)
+            },
+            (IRValue::Register(reg), IRValue::Immediate(imm)) =>
+            {
+                format!("    {}i {}, {}, {}\n", mnemonic, dest, self.mapping.get(reg).unwrap(), imm.value)
+            },
+            // The value being shifted has to be in a register
+            (IRValue::Immediate(imm), IRValue::Register(reg)) =>
+            {
+                format!("    li t6, {}\n    {} {}, t6, {}\n", imm.value as i64, mnemonic, dest, self.mapping.get(reg).unwrap())
+            },
+            (IRValue::Register(reg0), IRValue::Register(reg1)) =>
+            {
+                format!("    {} {}, {}, {}\n", mnemonic, dest, self.mapping.get(reg0).unwrap(), self.mapping.get(reg1).unwrap())
+            }
+        }
+    }
+}
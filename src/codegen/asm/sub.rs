@@ -12,7 +12,7 @@ impl AssemblyCodeGenerator
 
     pub fn sub_imm_reg(&self, dest: Register, src1: i64, src2: Register) -> String
     {
-        format!("    neg t6, {}\n    addi {}, {}, t6\n", src2, dest, src1)
+        format!("    li t6, {}\n    sub {}, t6, {}\n", src1, dest, src2)
     }
 
     pub fn sub_reg_reg(&self, dest: Register, src1: Register, src2: Register) -> String
@@ -28,11 +28,11 @@ impl AssemblyCodeGenerator
         }
         else if let (IRValue::Immediate(imm), IRValue::Register(reg)) = (src1, src2)
         {
-            self.sub_reg_imm(dest, *self.mapping.get(&reg).unwrap(), imm.value as i64)
+            self.sub_imm_reg(dest, imm.value as i64, *self.mapping.get(&reg).unwrap())
         }
         else if let (IRValue::Register(reg), IRValue::Immediate(imm)) = (src1, src2)
         {
-            self.sub_imm_reg(dest, imm.value as i64, *self.mapping.get(&reg).unwrap())
+            self.sub_reg_imm(dest, *self.mapping.get(&reg).unwrap(), imm.value as i64)
         }
         else if let (IRValue::Register(reg0), IRValue::Register(reg1)) = (src1, src2)
         {
@@ -1,11 +1,15 @@
+use std::collections::HashMap;
+
 use crate::parser::*;
 use crate::tokenizer::*;
 use crate::errors::*;
+use crate::settings::Target;
 
 use super::*;
+use super::lvalue::IRLvalue;
 
-/// Convert a parse tree into intermediate representation
-pub fn parse_tree_to_ir(tree: ParseTreeNode) -> CompilerResult<IR>
+/// Convert a parse tree into intermediate representation for the given target
+pub fn parse_tree_to_ir(tree: ParseTreeNode, target: Target) -> CompilerResult<IR>
 {
     trace!("Convert to Intermediate Representation");
 
@@ -15,7 +19,7 @@ pub fn parse_tree_to_ir(tree: ParseTreeNode) -> CompilerResult<IR>
     {
         for child in children
         {
-            ir.functions.push(parse_tree_function_to_ir(child)?);
+            ir.functions.push(parse_tree_function_to_ir(child, target)?);
         }
     }
     else
@@ -27,11 +31,11 @@ pub fn parse_tree_to_ir(tree: ParseTreeNode) -> CompilerResult<IR>
 }
 
 /// Convert a function parse tree node into an IRFunction
-pub fn parse_tree_function_to_ir(tree: ParseTreeNode) -> CompilerResult<IRFunction>
+pub fn parse_tree_function_to_ir(tree: ParseTreeNode, target: Target) -> CompilerResult<IRFunction>
 {
     if let ParseTreeNode::Function { name, return_type, child, arguments, .. } = tree
     {
-        IRFunction::with_statement_and_args(name, return_type, *child, arguments)
+        IRFunction::with_statement_and_args(name, return_type, *child, arguments, target.pointer_size())
     }
     else
     {
@@ -41,7 +45,7 @@ pub fn parse_tree_function_to_ir(tree: ParseTreeNode) -> CompilerResult<IRFuncti
 
 impl IRFunction
 {
-    pub fn with_statement_and_args(name: String, return_type: ValueType, statement: ParseTreeNode, arguments: Vec<(String, ValueType, Token)>) -> CompilerResult<Self>
+    pub fn with_statement_and_args(name: String, return_type: ValueType, statement: ParseTreeNode, arguments: Vec<(String, ValueType, Token)>, pointer_size: usize) -> CompilerResult<Self>
     {
         let mut result = Self
         {
@@ -50,37 +54,75 @@ impl IRFunction
             blocks: vec![IRBlock::new(0)],
             current_block: 0,
             scope_stack: Vec::new(),
+            loop_stack: Vec::new(),
+            switch_stack: Vec::new(),
+            labels: HashMap::new(),
             next_register: 0,
-            next_block: 1
+            next_block: 1,
+            arguments: arguments.len(),
+            pointer_size
         };
 
-        let scope = IRScope::from_arguments(arguments, &mut result);
+        result.scope_stack.push(IRScope::new());
+
+        for (arg_name, arg_type, arg_token) in arguments
+        {
+            result.declare_variable(&arg_name, arg_type, &arg_token)?;
+        }
+
+        // The arguments share a scope with the outermost block of the body
+        match statement
+        {
+            ParseTreeNode::StatementBlock { children } =>
+            {
+                for child in children
+                {
+                    result.add_statement(child)?;
+                }
+            },
+            statement => result.add_statement(statement)?
+        }
 
-        result.scope_stack.push(scope);
+        result.resolve_labels()?;
 
-        result.add_statement(statement)?;
+        // Falling off the end of the function returns, rather than running into whichever block is laid out next
+        if !matches!(result.mut_current_block().instructions.last(), Some(IRInstruction::Return { .. }))
+        {
+            result.mut_current_block().add_instruction(IRInstruction::Return { value: IRValue::Immediate(Value::code_constant(0)) });
+        }
 
         Ok(result)
     }
 
-    pub fn get_variable_value(&mut self, expression: &ParseTreeNode) -> CompilerResult<IRValue>
+    /// Declare a variable in the innermost scope, giving it a register of its own
+    pub fn declare_variable(&mut self, name: &str, value_type: ValueType, token: &Token) -> CompilerResult<usize>
     {
-        if let ParseTreeNode::VariableExpression { name, token } = expression
+        if let Some(previous) = self.scope_stack.last().and_then(|scope| scope.access_variable(name))
         {
-            let mut result = None;
+            return Err(CodegenError::compile_error(format!("Redeclaration of variable {} (previously declared at {})", name, previous.location), token).into());
+        }
 
-            for scope in self.scope_stack.iter().rev()
-            {
-                result = result.or(scope.access_variable(&name));
-                if result.is_some()
-                {
-                    break;
-                }
-            }
+        let register = self.alloc_next_register();
+        let variable = IRVariable { register, value_type, location: token.location.clone() };
+
+        self.scope_stack.last_mut().expect("Variables must be declared inside a scope").add_variable(name.to_string(), variable);
+
+        Ok(register)
+    }
+
+    /// Find the variable a name refers to, searching from the innermost scope outwards
+    pub fn find_variable(&self, name: &str) -> Option<&IRVariable>
+    {
+        self.scope_stack.iter().rev().find_map(|scope| scope.access_variable(name))
+    }
 
-            if let Some(result_value) = result
+    pub fn get_variable_value(&mut self, expression: &ParseTreeNode) -> CompilerResult<IRValue>
+    {
+        if let ParseTreeNode::VariableExpression { name, token } = expression
+        {
+            if let Some(variable) = self.find_variable(name)
             {
-                Ok(result_value)
+                Ok(IRValue::Register(variable.register))
             }
             else
             {
@@ -100,6 +142,35 @@ impl IRFunction
         Ok(())
     }
 
+    /// Branch to one of two blocks depending on whether a condition is nonzero
+    pub fn add_condition_branch(&mut self, condition: &ParseTreeNode, dest_true: usize, dest_false: usize) -> CompilerResult<()>
+    {
+        let mut value = self.generate_expression(condition)?;
+
+        // Branches compare a register, so constant conditions are moved into one first
+        if let IRValue::Immediate(_) = value
+        {
+            let register = IRValue::Register(self.alloc_next_register());
+            self.mut_current_block().add_instruction(IRInstruction::Add { dest: register.clone(), src1: value, src2: IRValue::Immediate(Value::code_constant(0)) });
+            value = register;
+        }
+
+        self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: value, src2: IRValue::Immediate(Value::code_constant(0)), dest_true, dest_false });
+
+        Ok(())
+    }
+
+    /// Generate the body of a loop, with `break` and `continue` jumping to the given blocks, and then jump to the
+    /// continue block
+    pub fn add_loop_body(&mut self, statement: ParseTreeNode, break_block: usize, continue_block: usize) -> CompilerResult<()>
+    {
+        self.loop_stack.push(IRLoopContext { break_block, continue_block: Some(continue_block) });
+        self.add_statement(statement)?;
+        self.loop_stack.pop();
+
+        self.add_jump(continue_block)
+    }
+
     pub fn add_three_op_instruction(&mut self, children: &Vec<ParseTreeNode>) -> CompilerResult<(IRValue, IRValue, IRValue)>
     {
         let src1 = self.generate_expression(&children[0])?;
@@ -114,11 +185,29 @@ impl IRFunction
     {
         match expression
         {
-            ParseTreeNode::ConstantExpression{ value, .. } => Ok(IRValue::Immediate(value.clone())),
+            ParseTreeNode::ConstantExpression{ value, token } =>
+            {
+                if value.value_type.value_type.is_floating()
+                {
+                    return Err(CodegenError::compile_error(format!("Floating point constant {} is not supported by the code generator", token.code_styled()), token).into());
+                }
+
+                Ok(IRValue::Immediate(value.clone()))
+            },
             ParseTreeNode::VariableExpression { .. } => self.get_variable_value(expression),
             ParseTreeNode::AdditiveExpression{operation, children, .. } =>
             {
-                let (dest, src1, src2) = self.add_three_op_instruction(children)?;
+                let (dest, mut src1, mut src2) = self.add_three_op_instruction(children)?;
+
+                // Adding an integer to a pointer moves it by whole elements
+                let types = (self.expression_type(&children[0]), self.expression_type(&children[1]));
+
+                match (types.0.dereferenced(), types.1.dereferenced())
+                {
+                    (Some(pointed), None) => src2 = self.scale_index(src2, self.type_size(&pointed)),
+                    (None, Some(pointed)) => src1 = self.scale_index(src1, self.type_size(&pointed)),
+                    _ => {}
+                }
 
                 match operation
                 {
@@ -146,6 +235,101 @@ impl IRFunction
 
                 Ok(dest)
             },
+            ParseTreeNode::ShiftExpression { operation, children, .. } =>
+            {
+                let arithmetic = self.expression_type(&children[0]).value_type.is_signed();
+                let (dest, src1, src2) = self.add_three_op_instruction(children)?;
+
+                match operation
+                {
+                    ShiftExpressionOperation::ShiftLeft => self.mut_current_block().add_instruction(
+                        IRInstruction::ShiftLeft { dest: dest.clone(), src1, src2 }),
+                    ShiftExpressionOperation::ShiftRight => self.mut_current_block().add_instruction(
+                        IRInstruction::ShiftRight { dest: dest.clone(), src1, src2, arithmetic }),
+                }
+
+                Ok(dest)
+            },
+            ParseTreeNode::AndExpression { children, .. } =>
+            {
+                let (dest, src1, src2) = self.add_three_op_instruction(children)?;
+
+                self.mut_current_block().add_instruction(IRInstruction::And { dest: dest.clone(), src1, src2 });
+
+                Ok(dest)
+            },
+            ParseTreeNode::XorExpression { children, .. } =>
+            {
+                let (dest, src1, src2) = self.add_three_op_instruction(children)?;
+
+                self.mut_current_block().add_instruction(IRInstruction::Xor { dest: dest.clone(), src1, src2 });
+
+                Ok(dest)
+            },
+            ParseTreeNode::OrExpression { children, .. } =>
+            {
+                let (dest, src1, src2) = self.add_three_op_instruction(children)?;
+
+                self.mut_current_block().add_instruction(IRInstruction::Or { dest: dest.clone(), src1, src2 });
+
+                Ok(dest)
+            },
+            ParseTreeNode::AssignmentExpression { operation, children, optoken } => self.generate_assignment(operation, children, optoken),
+            ParseTreeNode::UnaryExpression { operation, child, optoken } =>
+            {
+                match operation
+                {
+                    UnaryExpressionOperation::Dereference =>
+                    {
+                        let lvalue = self.generate_lvalue(expression, optoken)?;
+
+                        Ok(self.load_lvalue(&lvalue))
+                    },
+                    UnaryExpressionOperation::Increment => self.generate_increment(child, optoken, true, false),
+                    UnaryExpressionOperation::Decrement => self.generate_increment(child, optoken, false, false),
+                    UnaryExpressionOperation::Positive => self.generate_expression(child),
+                    UnaryExpressionOperation::Negation =>
+                    {
+                        let src2 = self.generate_expression(child)?;
+                        let dest = IRValue::Register(self.alloc_next_register());
+
+                        self.mut_current_block().add_instruction(IRInstruction::Sub { dest: dest.clone(), src1: IRValue::Immediate(Value::code_constant(0)), src2 });
+
+                        Ok(dest)
+                    },
+                    UnaryExpressionOperation::BitwiseNot =>
+                    {
+                        let src1 = self.generate_expression(child)?;
+                        let dest = IRValue::Register(self.alloc_next_register());
+
+                        self.mut_current_block().add_instruction(IRInstruction::Xor { dest: dest.clone(), src1, src2: IRValue::Immediate(Value::integer_constant(u64::MAX, RawValueType::I64)) });
+
+                        Ok(dest)
+                    },
+                    UnaryExpressionOperation::LogicalNot =>
+                    {
+                        // Comparisons need their first operand in a register
+                        let src1 = self.generate_expression(child)?;
+                        let src1 = self.value_in_register(src1);
+                        let dest = IRValue::Register(self.alloc_next_register());
+
+                        self.mut_current_block().add_instruction(IRInstruction::Conditional { condition: IRBranchCondition::Equal, dest: dest.clone(), src1, src2: IRValue::Immediate(Value::code_constant(0)) });
+
+                        Ok(dest)
+                    },
+                    // Only objects in memory have addresses, variables are held in registers
+                    UnaryExpressionOperation::Reference => match self.generate_lvalue(child, optoken)?
+                    {
+                        IRLvalue::Memory { address, .. } => Ok(address),
+                        IRLvalue::Register(_) =>
+                        {
+                            let name = child.get_variable_name().unwrap_or_default();
+
+                            Err(CodegenError::compile_error(format!("Cannot take the address of {}, as variables are held in registers rather than memory", name), optoken).into())
+                        }
+                    }
+                }
+            },
             ParseTreeNode::EqualityExpression { operation, children, .. } =>
             {
                 let (dest, src1, src2) = self.add_three_op_instruction(children)?;
@@ -178,15 +362,18 @@ impl IRFunction
 
                 Ok(dest)
             }
-            ParseTreeNode::PostfixExpression { operation, children, .. } =>
+            ParseTreeNode::PostfixExpression { operation, children, optoken } =>
             {
                 match operation
                 {
-                    PostfixExpressionOperation::ArrayIndexing => todo!(),
-                    PostfixExpressionOperation::MemberAccess => todo!(),
-                    PostfixExpressionOperation::IndirectMemberAccess => todo!(),
-                    PostfixExpressionOperation::Increment => todo!(),
-                    PostfixExpressionOperation::Decrement => todo!(),
+                    PostfixExpressionOperation::ArrayIndexing | PostfixExpressionOperation::MemberAccess | PostfixExpressionOperation::IndirectMemberAccess =>
+                    {
+                        let lvalue = self.generate_lvalue(expression, optoken)?;
+
+                        Ok(self.load_lvalue(&lvalue))
+                    },
+                    PostfixExpressionOperation::Increment => self.generate_increment(&children[0], optoken, true, true),
+                    PostfixExpressionOperation::Decrement => self.generate_increment(&children[0], optoken, false, true),
                     PostfixExpressionOperation::InitializerList => todo!(),
                     PostfixExpressionOperation::FunctionCall => 
                     {
@@ -211,28 +398,16 @@ impl IRFunction
                             argument_values.push(arg_reg);
                         }
 
-                        // Backup the argument registers
-                        for reg_number in 0..argument_count
-                        {
-                            if reg_number == dest_reg_num { continue; }
-                            self.mut_current_block().add_instruction(IRInstruction::Backup { register: reg_number })
-                        }
-
+                        // Registers which are live across the call are backed up around it once the function is allocated
                         self.mut_current_block().add_instruction(IRInstruction::FunctionCall { name: children[0].get_variable_name().unwrap(), arguments: argument_values.clone() });
 
                         self.mut_current_block().add_instruction(IRInstruction::LoadRet { dest: dest.clone() });
 
-                        // Restore the argument registers
-                        for reg_number in (0..argument_count).rev()
-                        {
-                            if reg_number == dest_reg_num { continue; }
-                            self.mut_current_block().add_instruction(IRInstruction::Restore { register: reg_number })
-                        }
-
                         Ok(dest)
                     }
                 }
             },
+            ParseTreeNode::LabelAddressExpression { name, token } => Ok(self.generate_label_address(name, token)),
             _ => 
             {
                 error!("Unhandled Expression Type {}", expression);
@@ -246,6 +421,30 @@ impl IRFunction
         match statement
         {
             ParseTreeNode::StatementBlock { children } => 
+            {
+                // Each block has its own scope, so the names declared in it may shadow those outside
+                self.scope_stack.push(IRScope::new());
+
+                for child in children
+                {
+                    self.add_statement(child)?;
+                }
+
+                self.scope_stack.pop();
+
+                Ok(())
+            },
+            ParseTreeNode::ExpressionStatement { child } =>
+            {
+                // The value of an expression statement is discarded, it is only evaluated for its side effects
+                if let Some(expression) = child
+                {
+                    self.generate_expression(&expression)?;
+                }
+
+                Ok(())
+            },
+            ParseTreeNode::Declaration { children } =>
             {
                 for child in children
                 {
@@ -254,6 +453,20 @@ impl IRFunction
 
                 Ok(())
             },
+            ParseTreeNode::Declarator { name, value_type, child, name_token } =>
+            {
+                // The name is in scope from the end of its declarator, so before its initializer
+                let register = self.declare_variable(&name, value_type, &name_token)?;
+
+                if let Some(initializer) = child
+                {
+                    let value = self.generate_expression(&initializer)?;
+
+                    self.mut_current_block().add_instruction(IRInstruction::Add { dest: IRValue::Register(register), src1: value, src2: IRValue::Immediate(Value::code_constant(0)) });
+                }
+
+                Ok(())
+            },
             ParseTreeNode::ReturnStatement { child } => 
             {
                 if let Some(expression) = child
@@ -280,17 +493,18 @@ impl IRFunction
                 // Allocate the true branch
                 let true_branch = self.alloc_next_block();
 
-                // Write the true statement to the true branch
+                // Write the true statement to the true branch, which may leave us in a later block if it has control flow
+                // of its own
                 self.current_block = true_branch;
                 self.add_statement(statement.clone())?;
+                let true_end = self.current_block;
 
                 // Allocate the false branch
                 let false_branch = self.alloc_next_block();
 
                 // Add the conditional jump instruction to the initial block
                 self.current_block = initial_block;
-                let condition_value = self.generate_expression(condition)?;
-                self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: condition_value, src2: IRValue::Immediate(Value::code_constant(0)), dest_true: true_branch, dest_false: false_branch });
+                self.add_condition_branch(condition, true_branch, false_branch)?;
 
                 // We need to know what branch should be skipped to when we write to the true branch
                 let skip_branch;
@@ -309,14 +523,136 @@ impl IRFunction
                     skip_branch = false_branch;
                 }
 
-                // Add the jump to the skip branch to the true branch
-                self.current_block = true_branch;
+                // Add the jump to the skip branch to the end of the true branch
+                self.current_block = true_end;
                 self.add_jump(skip_branch)?;
 
                 self.current_block = skip_branch;
 
                 Ok(())
             },
+            ParseTreeNode::WhileLoop { mut children } =>
+            {
+                let statement = children.pop().expect("WhileLoop needs two children");
+                let condition = children.pop().expect("WhileLoop needs two children");
+
+                let condition_block = self.alloc_next_block();
+                let body_block = self.alloc_next_block();
+                let exit_block = self.alloc_next_block();
+
+                // The condition is checked before every iteration, including the first
+                self.add_jump(condition_block)?;
+
+                self.current_block = condition_block;
+                self.add_condition_branch(&condition, body_block, exit_block)?;
+
+                self.current_block = body_block;
+                self.add_loop_body(statement, exit_block, condition_block)?;
+
+                self.current_block = exit_block;
+
+                Ok(())
+            },
+            ParseTreeNode::DoWhileLoop { mut children } =>
+            {
+                let condition = children.pop().expect("DoWhileLoop needs two children");
+                let statement = children.pop().expect("DoWhileLoop needs two children");
+
+                let body_block = self.alloc_next_block();
+                let condition_block = self.alloc_next_block();
+                let exit_block = self.alloc_next_block();
+
+                // The body always runs once before the condition is checked
+                self.add_jump(body_block)?;
+
+                self.current_block = body_block;
+                self.add_loop_body(statement, exit_block, condition_block)?;
+
+                self.current_block = condition_block;
+                self.add_condition_branch(&condition, body_block, exit_block)?;
+
+                self.current_block = exit_block;
+
+                Ok(())
+            },
+            ParseTreeNode::ForLoop { init, condition, step, child } =>
+            {
+                // Names declared in the first clause are only in scope for the loop
+                self.scope_stack.push(IRScope::new());
+                self.add_statement(*init)?;
+
+                let condition_block = self.alloc_next_block();
+                let body_block = self.alloc_next_block();
+                let step_block = self.alloc_next_block();
+                let exit_block = self.alloc_next_block();
+
+                self.add_jump(condition_block)?;
+
+                // A missing condition is always true
+                self.current_block = condition_block;
+
+                match condition
+                {
+                    Some(condition) => self.add_condition_branch(&condition, body_block, exit_block)?,
+                    None => self.add_jump(body_block)?
+                }
+
+                // Continuing runs the step before checking the condition again
+                self.current_block = body_block;
+                self.add_loop_body(*child, exit_block, step_block)?;
+
+                self.current_block = step_block;
+
+                if let Some(step) = step
+                {
+                    self.generate_expression(&step)?;
+                }
+
+                self.add_jump(condition_block)?;
+
+                self.current_block = exit_block;
+                self.scope_stack.pop();
+
+                Ok(())
+            },
+            ParseTreeNode::BreakStatement { token } | ParseTreeNode::ContinueStatement { token } =>
+            {
+                // Switches can be broken out of, but continuing goes to the loop around them
+                let (dest, enclosing) = match token.token_type
+                {
+                    TokenType::Keyword(Keyword::Break) => (self.loop_stack.last().map(|context| context.break_block), "loop or switch"),
+                    _ => (self.loop_stack.iter().rev().find_map(|context| context.continue_block), "loop")
+                };
+
+                let dest = dest.ok_or_else(|| CodegenError::compile_error(format!("{} statement is not within a {}", token.code_styled(), enclosing), &token))?;
+
+                self.add_jump(dest)?;
+
+                // Anything after the jump is unreachable, so it is given a block of its own
+                self.current_block = self.alloc_next_block();
+
+                Ok(())
+            },
+            ParseTreeNode::SwitchStatement { children, token } => self.add_switch(children, &token),
+            ParseTreeNode::CaseStatement { mut children, token } =>
+            {
+                let statement = children.pop().expect("CaseStatement needs two children");
+
+                self.add_case_label(children.first(), &token)?;
+                self.add_statement(statement)
+            },
+            ParseTreeNode::DefaultStatement { child, token } =>
+            {
+                self.add_case_label(None, &token)?;
+                self.add_statement(*child)
+            },
+            ParseTreeNode::LabeledStatement { name, child, token } =>
+            {
+                self.add_label(&name, &token)?;
+                self.add_statement(*child)
+            },
+            ParseTreeNode::GotoStatement { name, token } => self.add_goto(&name, &token),
+            ParseTreeNode::ComputedGotoStatement { child, .. } => self.add_computed_goto(&child),
             _ => 
             {
                 error!("Unhandled statement {}", statement);
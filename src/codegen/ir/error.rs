@@ -7,8 +7,9 @@ pub struct CodegenError
 {
     pub error: CodegenErrorType,
     pub location: Location,
-    pub original_location: Option<Location>,
-    pub arrow_length: usize
+    pub original_location: Option<Box<Location>>,
+    /// Macro expansions the error happened in, innermost first
+    pub expansions: Vec<ExpansionFrame>
 }
 
 pub enum CodegenErrorType
@@ -44,8 +45,8 @@ impl CodegenError
         {
             location: token.location.clone(),
             error: CodegenErrorType::CompileError(error),
-            original_location: token.original_location.clone(),
-            arrow_length: token.code_styled().len(),
+            original_location: token.original_location.clone().map(Box::new),
+            expansions: token.expansions.clone(),
         }
     }
 }
\ No newline at end of file
@@ -31,16 +31,25 @@ impl IR
     }
 }
 
+/// Variable which is held in a register
+#[derive(Debug, Clone)]
+pub struct IRVariable
+{
+    pub register: usize,
+    pub value_type: ValueType,
+    /// Where the variable was declared
+    pub location: Location
+}
+
 /// Scope for the Intermediate Representation Code Generation
 #[derive(Debug, Clone)]
 pub struct IRScope
 {
-    variables: HashMap<String, usize>
+    variables: HashMap<String, IRVariable>
 }
 
 impl IRScope
 {
-    #[allow(dead_code)]
     pub fn new() -> Self
     {
         Self
@@ -49,28 +58,51 @@ impl IRScope
         }
     }
 
-    pub fn from_arguments(arguments: Vec<(String, ValueType, Token)>, function: &mut IRFunction) -> Self
+    pub fn add_variable(&mut self, name: String, variable: IRVariable)
     {
-        let mut variables = HashMap::new();
-
-        for (arg_name, _, _) in arguments
-        {
-            let reg = function.alloc_next_register();
-            variables.insert(arg_name, reg);
-        }
-
-        Self
-        {
-            variables
-        }
+        self.variables.insert(name, variable);
     }
 
-    pub fn access_variable(&self, name: &str) -> Option<IRValue>
+    pub fn access_variable(&self, name: &str) -> Option<&IRVariable>
     {
-        self.variables.get(name).map(|reg_num| IRValue::Register(*reg_num))
+        self.variables.get(name)
     }
 }
 
+/// Blocks which `break` and `continue` jump to inside a loop or switch
+#[derive(Debug, Clone)]
+pub struct IRLoopContext
+{
+    pub break_block: usize,
+    /// None for a switch, which `continue` passes through to the loop around it
+    pub continue_block: Option<usize>
+}
+
+/// Labels found so far in the body of a switch statement
+#[derive(Debug, Clone)]
+pub struct IRSwitchContext
+{
+    /// Value of each case along with the block it starts and where it was written
+    pub cases: Vec<(i64, usize, Location)>,
+    pub default: Option<(usize, Location)>,
+    /// Promoted type of the controlling expression, which case values are converted to
+    pub value_type: RawValueType
+}
+
+/// Label of a statement, which goto can jump to from anywhere in the function
+#[derive(Debug, Clone)]
+pub struct IRLabel
+{
+    /// Block the labeled statement starts, which is allocated when the label is first mentioned
+    pub block: usize,
+    /// Where the label was defined, None until its statement is reached
+    pub definition: Option<Location>,
+    /// First goto or address of the label, if it has been used
+    pub first_use: Option<Token>,
+    /// True if the address of the label is taken with `&&`, so computed gotos may jump to it
+    pub address_taken: bool
+}
+
 /// Intermediate Representation Function
 #[derive(Debug, Clone)]
 pub struct IRFunction
@@ -80,8 +112,18 @@ pub struct IRFunction
     pub blocks: Vec<IRBlock>,
     pub current_block: usize,
     pub scope_stack: Vec<IRScope>,
+    /// Loops enclosing the statement being generated, innermost last
+    pub loop_stack: Vec<IRLoopContext>,
+    /// Switches enclosing the statement being generated, innermost last
+    pub switch_stack: Vec<IRSwitchContext>,
+    /// Labels of the function by name, which may be used before they are defined
+    pub labels: HashMap<String, IRLabel>,
     pub next_register: usize,
     pub next_block: usize,
+    /// Number of arguments, which are held in the first registers
+    pub arguments: usize,
+    /// Size of a pointer on the target, in bytes
+    pub pointer_size: usize,
 }
 
 impl IRFunction
@@ -208,11 +250,31 @@ pub enum IRInstruction
     Mul { dest: IRValue, src1: IRValue, src2: IRValue },
     Div { dest: IRValue, src1: IRValue, src2: IRValue },
     Mod { dest: IRValue, src1: IRValue, src2: IRValue },
+    And { dest: IRValue, src1: IRValue, src2: IRValue },
+    Or { dest: IRValue, src1: IRValue, src2: IRValue },
+    Xor { dest: IRValue, src1: IRValue, src2: IRValue },
+    ShiftLeft { dest: IRValue, src1: IRValue, src2: IRValue },
+    /// Arithmetic shifts copy the sign bit into the vacated bits, logical shifts fill them with zeros
+    ShiftRight { dest: IRValue, src1: IRValue, src2: IRValue, arithmetic: bool },
+    /// Load a value of the given size from the address held in a register, sign extending it if it is signed
+    Load { dest: IRValue, address: IRValue, size: usize, signed: bool },
+    /// Store the low bytes of a value to the address held in a register
+    Store { src: IRValue, address: IRValue, size: usize },
     Jump { dest: usize },
+    /// Jump to the block in a table of targets at the given offset in bytes
+    JumpTable { offset: IRValue, targets: Vec<usize> },
+    /// Jump to the address held in a value, which is the start of one of the targets
+    IndirectJump { address: IRValue, targets: Vec<usize> },
+    /// Load the address of the start of a block
+    LoadLabel { dest: IRValue, block: usize },
     Branch { condition: IRBranchCondition, src1: IRValue,  src2: IRValue, dest_true: usize, dest_false: usize },
     Conditional { condition: IRBranchCondition, dest: IRValue, src1: IRValue, src2: IRValue },
     Backup { register: usize },
     Restore { register: usize },
+    /// Store a value to a stack slot, as there are too few registers to keep it in one throughout the function
+    Spill { src: IRValue, slot: usize },
+    /// Load a spilled value back from its stack slot
+    Reload { dest: IRValue, slot: usize },
     FunctionCall { name: String, arguments: Vec<IRValue> },
     LoadRet { dest: IRValue }
 }
@@ -229,11 +291,29 @@ impl std::fmt::Display for IRInstruction
             IRInstruction::Mul { dest, src1, src2 } => write!(f, "mul     {}, {}, {}", dest ,src1, src2),
             IRInstruction::Div { dest, src1, src2 } => write!(f, "div     {}, {}, {}", dest ,src1, src2),
             IRInstruction::Mod { dest, src1, src2 } => write!(f, "mod     {}, {}, {}", dest ,src1, src2),
+            IRInstruction::And { dest, src1, src2 } => write!(f, "and     {}, {}, {}", dest ,src1, src2),
+            IRInstruction::Or { dest, src1, src2 } => write!(f, "or      {}, {}, {}", dest ,src1, src2),
+            IRInstruction::Xor { dest, src1, src2 } => write!(f, "xor     {}, {}, {}", dest ,src1, src2),
+            IRInstruction::ShiftLeft { dest, src1, src2 } => write!(f, "shl     {}, {}, {}", dest ,src1, src2),
+            IRInstruction::ShiftRight { dest, src1, src2, arithmetic } => write!(f, "{}     {}, {}, {}", if *arithmetic {"sar"} else {"shr"}, dest ,src1, src2),
+            IRInstruction::Load { dest, address, size, signed } => write!(f, "load{}{:<3} {}, [{}]", if *signed {"s"} else {"u"}, size, dest, address),
+            IRInstruction::Store { src, address, size } => write!(f, "store{:<3} {}, [{}]", size, src, address),
             IRInstruction::Jump { dest } => write!(f, "j       L{}", dest),
+            IRInstruction::JumpTable { offset, targets } =>
+            {
+                write!(f, "jt      {}, [{}]", offset, targets.iter().map(|target| format!("L{}", target)).collect::<Vec<_>>().join(", "))
+            },
+            IRInstruction::IndirectJump { address, targets } =>
+            {
+                write!(f, "jr      {}, [{}]", address, targets.iter().map(|target| format!("L{}", target)).collect::<Vec<_>>().join(", "))
+            },
+            IRInstruction::LoadLabel { dest, block } => write!(f, "la      {}, L{}", dest, block),
             IRInstruction::Branch { condition, src1, src2, dest_true, dest_false } => write!(f, "b{}     {}, {}, L{}, L{}", condition, src1, src2, dest_true, dest_false),
             IRInstruction::Conditional { condition, dest, src1, src2 } => write!(f, "s{}     {}, {}, {}", condition, dest, src1, src2),
             IRInstruction::Backup { register } => write!(f, "backup  {}", IRValue::Register(*register)),
             IRInstruction::Restore { register } => write!(f, "restore {}", IRValue::Register(*register)),
+            IRInstruction::Spill { src, slot } => write!(f, "spill   {}, S{}", src, slot),
+            IRInstruction::Reload { dest, slot } => write!(f, "reload  {}, S{}", dest, slot),
             IRInstruction::LoadRet { dest } => write!(f, "loadret {}", dest),
             IRInstruction::FunctionCall { name, arguments } => 
             {
@@ -0,0 +1,123 @@
+use crate::parser::*;
+use crate::tokenizer::*;
+use crate::errors::*;
+
+use super::*;
+
+impl IRFunction
+{
+    /// Get the block a label starts, allocating it the first time the label is mentioned
+    fn label_block(&mut self, name: &str) -> usize
+    {
+        if let Some(label) = self.labels.get(name)
+        {
+            return label.block;
+        }
+
+        let block = self.alloc_next_block();
+        self.labels.insert(name.to_string(), IRLabel { block, definition: None, first_use: None, address_taken: false });
+
+        block
+    }
+
+    /// Get the block a label starts for a goto or address of it, which may come before the label is defined
+    fn use_label(&mut self, name: &str, token: &Token) -> &mut IRLabel
+    {
+        self.label_block(name);
+
+        let label = self.labels.get_mut(name).expect("Label was added above");
+        label.first_use.get_or_insert_with(|| token.clone());
+
+        label
+    }
+
+    /// Start the block of a labeled statement, which the statements before it fall through into
+    pub fn add_label(&mut self, name: &str, token: &Token) -> CompilerResult<()>
+    {
+        let block = self.label_block(name);
+        let label = self.labels.get_mut(name).expect("Label was added above");
+
+        if let Some(previous) = &label.definition
+        {
+            return Err(CodegenError::compile_error(format!("Duplicate label {} (previously defined at {})", name, previous), token).into());
+        }
+
+        label.definition = Some(token.location.clone());
+
+        self.add_jump(block)?;
+        self.current_block = block;
+
+        Ok(())
+    }
+
+    /// Jump to a label anywhere in the function
+    pub fn add_goto(&mut self, name: &str, token: &Token) -> CompilerResult<()>
+    {
+        let block = self.use_label(name, token).block;
+
+        self.add_jump(block)?;
+
+        // Anything after the jump is unreachable, so it is given a block of its own
+        self.current_block = self.alloc_next_block();
+
+        Ok(())
+    }
+
+    /// Jump to an address computed from the addresses of labels
+    pub fn add_computed_goto(&mut self, expression: &ParseTreeNode) -> CompilerResult<()>
+    {
+        let address = self.generate_expression(expression)?;
+        let address = self.value_in_register(address);
+
+        // Which blocks it might jump to is only known once the addresses of all the labels have been taken
+        self.mut_current_block().add_instruction(IRInstruction::IndirectJump { address, targets: Vec::new() });
+
+        self.current_block = self.alloc_next_block();
+
+        Ok(())
+    }
+
+    /// Load the address of a label, for a computed goto to jump to
+    pub fn generate_label_address(&mut self, name: &str, token: &Token) -> IRValue
+    {
+        let label = self.use_label(name, token);
+        label.address_taken = true;
+
+        let block = label.block;
+        let dest = IRValue::Register(self.alloc_next_register());
+
+        self.mut_current_block().add_instruction(IRInstruction::LoadLabel { dest: dest.clone(), block });
+
+        dest
+    }
+
+    /// Check that every label which was used has been defined, and let computed gotos jump to any label whose address
+    /// was taken, once the whole body has been generated
+    pub fn resolve_labels(&mut self) -> CompilerResult<()>
+    {
+        let mut undefined = self.labels.iter().filter(|(_, label)| label.definition.is_none())
+            .filter_map(|(name, label)| label.first_use.as_ref().map(|token| (name, token)))
+            .collect::<Vec<_>>();
+
+        // The first undefined label in the source is reported, regardless of the order of the table
+        undefined.sort_by_key(|(_, token)| (token.location.line, token.location.column));
+
+        if let Some((name, token)) = undefined.first()
+        {
+            return Err(CodegenError::compile_error(format!("Label {} is used but never defined", name), token).into());
+        }
+
+        let mut targets = self.labels.values().filter(|label| label.address_taken).map(|label| label.block).collect::<Vec<_>>();
+        targets.sort_unstable();
+
+        for instruction in self.blocks.iter_mut().flat_map(|block| block.instructions.iter_mut())
+        {
+            if let IRInstruction::IndirectJump { targets: jump_targets, .. } = instruction
+            {
+                *jump_targets = targets.clone();
+            }
+        }
+
+        Ok(())
+    }
+}
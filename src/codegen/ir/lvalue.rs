@@ -0,0 +1,314 @@
+use crate::parser::*;
+use crate::tokenizer::*;
+use crate::errors::*;
+
+use super::*;
+
+/// Place which a value can be assigned to
+#[derive(Debug, Clone)]
+pub enum IRLvalue
+{
+    /// Variable held in a register
+    Register(usize),
+    /// Object in memory, at the address held in a register
+    Memory { address: IRValue, value_type: ValueType }
+}
+
+impl IRFunction
+{
+    /// Size in bytes of a value of the given type
+    pub fn type_size(&self, value_type: &ValueType) -> usize
+    {
+        if value_type.references > 0
+        {
+            self.pointer_size
+        }
+        else
+        {
+            value_type.value_type.size()
+        }
+    }
+
+    /// Work out the type of an expression, as far as the code generator needs it to size memory accesses and scale
+    /// pointer arithmetic
+    ///
+    /// Integer promotions and the usual arithmetic conversions aren't applied, so arithmetic takes the type of its left
+    /// operand.
+    pub fn expression_type(&self, expression: &ParseTreeNode) -> ValueType
+    {
+        match expression
+        {
+            ParseTreeNode::ConstantExpression { value, .. } => value.value_type.clone(),
+            ParseTreeNode::VariableExpression { name, .. } =>
+            {
+                self.find_variable(name).map(|variable| variable.value_type.clone()).unwrap_or_else(|| RawValueType::I32.into())
+            },
+            ParseTreeNode::UnaryExpression { operation: UnaryExpressionOperation::Dereference, child, .. } =>
+            {
+                self.expression_type(child).dereferenced().unwrap_or_else(|| RawValueType::I32.into())
+            },
+            ParseTreeNode::UnaryExpression { operation: UnaryExpressionOperation::Reference, child, .. } =>
+            {
+                let value_type = self.expression_type(child);
+
+                ValueType { references: value_type.references + 1, value_type: value_type.value_type }
+            },
+            ParseTreeNode::UnaryExpression { child, .. } => self.expression_type(child),
+            ParseTreeNode::PostfixExpression { operation: PostfixExpressionOperation::ArrayIndexing, children, .. } =>
+            {
+                children.iter().find_map(|child| self.expression_type(child).dereferenced()).unwrap_or_else(|| RawValueType::I32.into())
+            },
+            ParseTreeNode::PostfixExpression { operation: PostfixExpressionOperation::Increment | PostfixExpressionOperation::Decrement, children, .. } =>
+            {
+                self.expression_type(&children[0])
+            },
+            // Adding to a pointer gives another pointer of the same type
+            ParseTreeNode::AdditiveExpression { children, .. } =>
+            {
+                let types = children.iter().map(|child| self.expression_type(child)).collect::<Vec<_>>();
+
+                types.iter().find(|value_type| value_type.references > 0).unwrap_or(&types[0]).clone()
+            },
+            ParseTreeNode::MultiplicativeExpression { children, .. } | ParseTreeNode::ShiftExpression { children, .. } |
+            ParseTreeNode::AndExpression { children, .. } | ParseTreeNode::XorExpression { children, .. } |
+            ParseTreeNode::OrExpression { children, .. } | ParseTreeNode::AssignmentExpression { children, .. } =>
+            {
+                self.expression_type(&children[0])
+            },
+            ParseTreeNode::CommaExpression { children, .. } => self.expression_type(&children[1]),
+            ParseTreeNode::LabelAddressExpression { .. } => ValueType { references: 1, value_type: RawValueType::Void },
+            _ => RawValueType::I32.into()
+        }
+    }
+
+    /// Get the type a pointer expression points to, which must be a complete type
+    fn pointed_type(&self, pointer: &ParseTreeNode, token: &Token) -> CompilerResult<ValueType>
+    {
+        let pointer_type = self.expression_type(pointer);
+
+        match pointer_type.dereferenced()
+        {
+            Some(value_type) if self.type_size(&value_type) > 0 => Ok(value_type),
+            Some(_) => Err(CodegenError::compile_error(format!("Cannot access memory through {} of type {}", token.code_styled(), pointer_type), token).into()),
+            None => Err(CodegenError::compile_error(format!("Operand of {} is not a pointer, its type is {}", token.code_styled(), pointer_type), token).into())
+        }
+    }
+
+    /// Make sure a value is in a register, copying an immediate into a new one
+    pub fn value_in_register(&mut self, value: IRValue) -> IRValue
+    {
+        match value
+        {
+            IRValue::Register(_) => value,
+            IRValue::Immediate(_) =>
+            {
+                let dest = IRValue::Register(self.alloc_next_register());
+
+                self.mut_current_block().add_instruction(IRInstruction::Add { dest: dest.clone(), src1: value, src2: IRValue::Immediate(Value::code_constant(0)) });
+
+                dest
+            }
+        }
+    }
+
+    /// Multiply an index by the size of the elements it counts, so that it can be added to a pointer
+    pub fn scale_index(&mut self, index: IRValue, size: usize) -> IRValue
+    {
+        match index
+        {
+            _ if size == 1 => index,
+            IRValue::Immediate(value) => IRValue::Immediate(Value::integer_constant(value.value.wrapping_mul(size as u64), RawValueType::I64)),
+            IRValue::Register(_) =>
+            {
+                let dest = IRValue::Register(self.alloc_next_register());
+
+                self.mut_current_block().add_instruction(IRInstruction::Mul { dest: dest.clone(), src1: index, src2: IRValue::Immediate(Value::code_constant(size as u32)) });
+
+                dest
+            }
+        }
+    }
+
+    /// Find the place an lvalue expression refers to, where operator is the token of the operation needing an lvalue
+    pub fn generate_lvalue(&mut self, expression: &ParseTreeNode, operator: &Token) -> CompilerResult<IRLvalue>
+    {
+        match expression
+        {
+            ParseTreeNode::VariableExpression { name, token } => match self.find_variable(name)
+            {
+                Some(variable) => Ok(IRLvalue::Register(variable.register)),
+                None => Err(CodegenError::compile_error(format!("Variable {} is not defined", name), token).into())
+            },
+            ParseTreeNode::UnaryExpression { operation: UnaryExpressionOperation::Dereference, child, optoken } =>
+            {
+                let value_type = self.pointed_type(child, optoken)?;
+
+                let address = self.generate_expression(child)?;
+                let address = self.value_in_register(address);
+
+                Ok(IRLvalue::Memory { address, value_type })
+            },
+            ParseTreeNode::PostfixExpression { operation: PostfixExpressionOperation::ArrayIndexing, children, optoken } =>
+            {
+                // Either operand may be the pointer, as a[i] is the same as i[a]
+                let (pointer, index) = match self.expression_type(&children[0]).references
+                {
+                    0 => (&children[1], &children[0]),
+                    _ => (&children[0], &children[1])
+                };
+
+                let value_type = self.pointed_type(pointer, optoken)?;
+                let size = self.type_size(&value_type);
+
+                let base = self.generate_expression(pointer)?;
+                let index = self.generate_expression(index)?;
+                let offset = self.scale_index(index, size);
+
+                let address = IRValue::Register(self.alloc_next_register());
+                self.mut_current_block().add_instruction(IRInstruction::Add { dest: address.clone(), src1: base, src2: offset });
+
+                Ok(IRLvalue::Memory { address, value_type })
+            },
+            ParseTreeNode::PostfixExpression { operation: PostfixExpressionOperation::MemberAccess | PostfixExpressionOperation::IndirectMemberAccess, children, optoken } =>
+            {
+                let member = children[1].get_variable_name().unwrap_or_default();
+
+                Err(CodegenError::compile_error(format!("Member {} cannot be accessed, as structure layouts are not supported by the code generator", member), optoken).into())
+            },
+            _ => Err(CodegenError::compile_error(format!("Operand of {} is not assignable", operator.code_styled()), operator).into())
+        }
+    }
+
+    /// Read the value held by an lvalue
+    pub fn load_lvalue(&mut self, lvalue: &IRLvalue) -> IRValue
+    {
+        match lvalue
+        {
+            IRLvalue::Register(register) => IRValue::Register(*register),
+            IRLvalue::Memory { address, value_type } =>
+            {
+                let dest = IRValue::Register(self.alloc_next_register());
+                let size = self.type_size(value_type);
+                let signed = value_type.references == 0 && value_type.value_type.is_signed();
+
+                self.mut_current_block().add_instruction(IRInstruction::Load { dest: dest.clone(), address: address.clone(), size, signed });
+
+                dest
+            }
+        }
+    }
+
+    /// Write a value to an lvalue, giving back the value the assignment expression has
+    pub fn store_lvalue(&mut self, lvalue: &IRLvalue, value: IRValue) -> IRValue
+    {
+        match lvalue
+        {
+            IRLvalue::Register(register) =>
+            {
+                let dest = IRValue::Register(*register);
+
+                self.mut_current_block().add_instruction(IRInstruction::Add { dest: dest.clone(), src1: value, src2: IRValue::Immediate(Value::code_constant(0)) });
+
+                dest
+            },
+            IRLvalue::Memory { address, value_type } =>
+            {
+                let size = self.type_size(value_type);
+
+                self.mut_current_block().add_instruction(IRInstruction::Store { src: value.clone(), address: address.clone(), size });
+
+                value
+            }
+        }
+    }
+
+    /// Generate an assignment or compound assignment
+    pub fn generate_assignment(&mut self, operation: &AssignmentExpressionOperation, children: &[ParseTreeNode], optoken: &Token) -> CompilerResult<IRValue>
+    {
+        let target_type = self.expression_type(&children[0]);
+        let lvalue = self.generate_lvalue(&children[0], optoken)?;
+
+        if *operation == AssignmentExpressionOperation::Assignment
+        {
+            let value = self.generate_expression(&children[1])?;
+
+            return Ok(self.store_lvalue(&lvalue, value));
+        }
+
+        // Compound assignments read the current value, and store the result of the operation back to the same place
+        let current = self.load_lvalue(&lvalue);
+        let mut operand = self.generate_expression(&children[1])?;
+
+        // Adding to a pointer moves it by whole elements
+        if let Some(pointed) = target_type.dereferenced()
+        {
+            if matches!(operation, AssignmentExpressionOperation::AdditionAssignment | AssignmentExpressionOperation::SubtractionAssignment)
+            {
+                let size = self.type_size(&pointed);
+                operand = self.scale_index(operand, size);
+            }
+        }
+
+        let dest = IRValue::Register(self.alloc_next_register());
+        let (src1, src2) = (current, operand);
+
+        let instruction = match operation
+        {
+            AssignmentExpressionOperation::Assignment => unreachable!(),
+            AssignmentExpressionOperation::MultiplicationAssignment => IRInstruction::Mul { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::DivisionAssignment => IRInstruction::Div { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::ModulusAssignment => IRInstruction::Mod { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::AdditionAssignment => IRInstruction::Add { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::SubtractionAssignment => IRInstruction::Sub { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::ShiftLeftAssignment => IRInstruction::ShiftLeft { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::ShiftRightAssignment => IRInstruction::ShiftRight { dest: dest.clone(), src1, src2, arithmetic: target_type.value_type.is_signed() },
+            AssignmentExpressionOperation::AndAssignment => IRInstruction::And { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::XorAssignment => IRInstruction::Xor { dest: dest.clone(), src1, src2 },
+            AssignmentExpressionOperation::OrAssignment => IRInstruction::Or { dest: dest.clone(), src1, src2 },
+        };
+
+        self.mut_current_block().add_instruction(instruction);
+
+        Ok(self.store_lvalue(&lvalue, dest))
+    }
+
+    /// Generate an increment or decrement, which gives the value from before the change if it is postfix
+    pub fn generate_increment(&mut self, operand: &ParseTreeNode, optoken: &Token, increment: bool, postfix: bool) -> CompilerResult<IRValue>
+    {
+        let value_type = self.expression_type(operand);
+        let lvalue = self.generate_lvalue(operand, optoken)?;
+        let current = self.load_lvalue(&lvalue);
+
+        // Pointers step over a whole element
+        let step = match value_type.dereferenced()
+        {
+            Some(pointed) => self.type_size(&pointed),
+            None => 1
+        };
+
+        // A variable's register is about to change, so a postfix operation has to copy the old value out of it first
+        let previous = match lvalue
+        {
+            IRLvalue::Register(_) if postfix =>
+            {
+                let copy = IRValue::Register(self.alloc_next_register());
+                self.mut_current_block().add_instruction(IRInstruction::Add { dest: copy.clone(), src1: current.clone(), src2: IRValue::Immediate(Value::code_constant(0)) });
+                copy
+            },
+            _ => current.clone()
+        };
+
+        let dest = IRValue::Register(self.alloc_next_register());
+        let src2 = IRValue::Immediate(Value::code_constant(step as u32));
+
+        self.mut_current_block().add_instruction(match increment
+        {
+            true => IRInstruction::Add { dest: dest.clone(), src1: current, src2 },
+            false => IRInstruction::Sub { dest: dest.clone(), src1: current, src2 }
+        });
+
+        let updated = self.store_lvalue(&lvalue, dest);
+
+        Ok(if postfix { previous } else { updated })
+    }
+}
@@ -5,4 +5,10 @@ pub mod error;
 pub use error::*;
 
 pub mod intermediate_representation;
-pub use intermediate_representation::*;
\ No newline at end of file
+pub use intermediate_representation::*;
+
+pub mod labels;
+
+pub mod lvalue;
+
+pub mod switch;
@@ -0,0 +1,358 @@
+use crate::parser::*;
+use crate::tokenizer::*;
+use crate::errors::*;
+
+use super::*;
+
+/// Switches with at most this many cases test for each of them in turn
+const COMPARE_CHAIN_CASES: usize = 3;
+
+/// Largest number of entries a jump table may have
+const MAX_JUMP_TABLE_ENTRIES: u64 = 512;
+
+/// Jump tables are only used when at least one in this many of their entries has a case of its own
+const JUMP_TABLE_DENSITY: u64 = 3;
+
+/// Evaluate an integer constant expression such as a case value in 64 bit arithmetic, returning None if the expression
+/// isn't constant
+pub fn integer_constant_value(expression: &ParseTreeNode) -> Option<i64>
+{
+    let operands = |children: &[ParseTreeNode]| Some((integer_constant_value(&children[0])?, integer_constant_value(&children[1])?));
+
+    match expression
+    {
+        ParseTreeNode::ConstantExpression { value, .. } if !value.value_type.value_type.is_floating() => Some(value.value as i64),
+        ParseTreeNode::UnaryExpression { operation, child, .. } =>
+        {
+            let value = integer_constant_value(child)?;
+
+            match operation
+            {
+                UnaryExpressionOperation::Positive => Some(value),
+                UnaryExpressionOperation::Negation => Some(value.wrapping_neg()),
+                UnaryExpressionOperation::BitwiseNot => Some(!value),
+                UnaryExpressionOperation::LogicalNot => Some((value == 0) as i64),
+                _ => None
+            }
+        },
+        ParseTreeNode::MultiplicativeExpression { operation, children, .. } =>
+        {
+            let (left, right) = operands(children)?;
+
+            // Dividing by zero doesn't give a constant
+            match operation
+            {
+                MultiplicativeExpressionOperation::Multiplication => Some(left.wrapping_mul(right)),
+                MultiplicativeExpressionOperation::Division => left.checked_div(right),
+                MultiplicativeExpressionOperation::Modulus => left.checked_rem(right)
+            }
+        },
+        ParseTreeNode::AdditiveExpression { operation, children, .. } =>
+        {
+            let (left, right) = operands(children)?;
+
+            match operation
+            {
+                AdditiveExpressionOperation::Addition => Some(left.wrapping_add(right)),
+                AdditiveExpressionOperation::Subtraction => Some(left.wrapping_sub(right))
+            }
+        },
+        ParseTreeNode::ShiftExpression { operation, children, .. } =>
+        {
+            let (left, right) = operands(children)?;
+
+            match operation
+            {
+                ShiftExpressionOperation::ShiftLeft => Some(left.wrapping_shl(right as u32)),
+                ShiftExpressionOperation::ShiftRight => Some(left.wrapping_shr(right as u32))
+            }
+        },
+        ParseTreeNode::RelationalExpression { operation, children, .. } =>
+        {
+            let (left, right) = operands(children)?;
+
+            let result = match operation
+            {
+                RelationalExpressionOperation::LessThan => left < right,
+                RelationalExpressionOperation::GreaterThan => left > right,
+                RelationalExpressionOperation::LessThanOrEqual => left <= right,
+                RelationalExpressionOperation::GreaterThanOrEqual => left >= right
+            };
+
+            Some(result as i64)
+        },
+        ParseTreeNode::EqualityExpression { operation, children, .. } =>
+        {
+            let (left, right) = operands(children)?;
+
+            Some(((left == right) == (*operation == EqualityExpressionOperation::Equality)) as i64)
+        },
+        ParseTreeNode::AndExpression { children, .. } => operands(children).map(|(left, right)| left & right),
+        ParseTreeNode::XorExpression { children, .. } => operands(children).map(|(left, right)| left ^ right),
+        ParseTreeNode::OrExpression { children, .. } => operands(children).map(|(left, right)| left | right),
+        ParseTreeNode::LogicalAndExpression { children, .. } => operands(children).map(|(left, right)| (left != 0 && right != 0) as i64),
+        ParseTreeNode::LogicalOrExpression { children, .. } => operands(children).map(|(left, right)| (left != 0 || right != 0) as i64),
+        ParseTreeNode::ConditionalExpression { children, .. } =>
+        {
+            match integer_constant_value(&children[0])?
+            {
+                0 => integer_constant_value(&children[2]),
+                _ => integer_constant_value(&children[1])
+            }
+        },
+        _ => None
+    }
+}
+
+/// Convert a value to an integer type, wrapping it into the range of the type
+fn convert_to_type(value: i64, value_type: &RawValueType) -> i64
+{
+    let bits = value_type.size() * 8;
+
+    if bits >= 64
+    {
+        return value;
+    }
+
+    let truncated = value & ((1i64 << bits) - 1);
+
+    if value_type.is_signed() && truncated >= 1i64 << (bits - 1)
+    {
+        truncated - (1i64 << bits)
+    }
+    else
+    {
+        truncated
+    }
+}
+
+impl IRFunction
+{
+    /// Generate a switch statement, whose body is generated before the code which picks the case to jump to
+    pub fn add_switch(&mut self, mut children: Vec<ParseTreeNode>, token: &Token) -> CompilerResult<()>
+    {
+        let body = children.pop().expect("SwitchStatement needs two children");
+        let controlling = children.pop().expect("SwitchStatement needs two children");
+
+        let value_type = self.expression_type(&controlling);
+
+        if value_type.references > 0 || !(value_type.value_type.is_signed() || value_type.value_type.is_unsigned())
+        {
+            return Err(CodegenError::compile_error(format!("{} needs an integer to choose a case, but it was given a value of type {}", token.code_styled(), value_type), token).into());
+        }
+
+        // The controlling expression is promoted, so narrow values are compared as ints
+        let value_type = if value_type.value_type.size() < RawValueType::I32.size() { RawValueType::I32 } else { value_type.value_type };
+
+        let value = self.generate_expression(&controlling)?;
+        let value = self.value_in_register(value);
+        let dispatch_block = self.current_block;
+
+        let body_block = self.alloc_next_block();
+        let exit_block = self.alloc_next_block();
+
+        // The body is only entered through its labels, so anything before the first of them is unreachable
+        self.switch_stack.push(IRSwitchContext { cases: Vec::new(), default: None, value_type: value_type.clone() });
+        self.loop_stack.push(IRLoopContext { break_block: exit_block, continue_block: None });
+
+        self.current_block = body_block;
+        self.add_statement(body)?;
+        self.add_jump(exit_block)?;
+
+        self.loop_stack.pop();
+        let context = self.switch_stack.pop().expect("Switch context was pushed above");
+
+        let mut cases = context.cases.iter().map(|(case_value, block, _)| (*case_value, *block)).collect::<Vec<_>>();
+        cases.sort_unstable();
+
+        // Comparisons are signed, so unsigned values too large to be signed may only be tested for equality
+        let bits = value_type.size() * 8;
+        let ordered = value_type.is_signed() || cases.iter().all(|(case_value, _)| (0..=i64::MAX >> (64 - bits)).contains(case_value));
+
+        self.current_block = dispatch_block;
+        self.add_case_dispatch(&value, &cases, context.default.map(|(block, _)| block).unwrap_or(exit_block), ordered);
+
+        self.current_block = exit_block;
+
+        Ok(())
+    }
+
+    /// Start the block a case label, or the default label if there is no value, jumps to
+    ///
+    /// The statements before the label fall through into it.
+    pub fn add_case_label(&mut self, value: Option<&ParseTreeNode>, token: &Token) -> CompilerResult<()>
+    {
+        let context = self.switch_stack.last()
+            .ok_or_else(|| CodegenError::compile_error(format!("{} label is not within a switch statement", token.code_styled()), token))?;
+
+        let case_value = match value
+        {
+            Some(expression) =>
+            {
+                let case_value = integer_constant_value(expression)
+                    .ok_or_else(|| CodegenError::compile_error(format!("Value of {} label is not an integer constant expression", token.code_styled()), token))?;
+
+                // Cases are compared after being converted to the type of the controlling expression
+                let case_value = convert_to_type(case_value, &context.value_type);
+
+                if let Some((_, _, previous)) = context.cases.iter().find(|(other, _, _)| *other == case_value)
+                {
+                    return Err(CodegenError::compile_error(format!("Duplicate case value {} (previously used at {})", case_value, previous), token).into());
+                }
+
+                Some(case_value)
+            },
+            None =>
+            {
+                if let Some((_, previous)) = &context.default
+                {
+                    return Err(CodegenError::compile_error(format!("Multiple default labels in one switch (previously used at {})", previous), token).into());
+                }
+
+                None
+            }
+        };
+
+        let block = self.alloc_next_block();
+
+        self.add_jump(block)?;
+        self.current_block = block;
+
+        let context = self.switch_stack.last_mut().expect("Switch context was found above");
+
+        match case_value
+        {
+            Some(case_value) => context.cases.push((case_value, block, token.location.clone())),
+            None => context.default = Some((block, token.location.clone()))
+        }
+
+        Ok(())
+    }
+
+    /// Jump to the block of the case matching a value, or to the default block if none match, where the cases are sorted
+    /// by value
+    ///
+    /// Dense cases use a jump table, sparse ones a binary search, and a few cases are simply compared one after another.
+    fn add_case_dispatch(&mut self, value: &IRValue, cases: &[(i64, usize)], default: usize, ordered: bool)
+    {
+        if !ordered || cases.len() <= COMPARE_CHAIN_CASES
+        {
+            return self.add_case_chain(value, cases, default);
+        }
+
+        let entries = (cases[cases.len() - 1].0 as u64).wrapping_sub(cases[0].0 as u64).saturating_add(1);
+
+        if entries <= MAX_JUMP_TABLE_ENTRIES && entries <= cases.len() as u64 * JUMP_TABLE_DENSITY
+        {
+            self.add_case_jump_table(value, cases, default, entries as usize);
+        }
+        else
+        {
+            self.add_case_search(value, cases, default);
+        }
+    }
+
+    /// Compare a value with each case in turn
+    fn add_case_chain(&mut self, value: &IRValue, cases: &[(i64, usize)], default: usize)
+    {
+        for (i, (case_value, block)) in cases.iter().enumerate()
+        {
+            let equal = self.add_case_comparison(IRBranchCondition::Equal, value, *case_value);
+            let next = if i + 1 == cases.len() { default } else { self.alloc_next_block() };
+
+            self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: equal, src2: IRValue::Immediate(Value::code_constant(0)), dest_true: *block, dest_false: next });
+            self.current_block = next;
+        }
+
+        if cases.is_empty()
+        {
+            self.mut_current_block().add_instruction(IRInstruction::Jump { dest: default });
+        }
+    }
+
+    /// Split the cases in half by comparing the value with the middle one, until few enough are left to compare in turn
+    fn add_case_search(&mut self, value: &IRValue, cases: &[(i64, usize)], default: usize)
+    {
+        if cases.len() <= COMPARE_CHAIN_CASES
+        {
+            return self.add_case_chain(value, cases, default);
+        }
+
+        let (lower, upper) = cases.split_at(cases.len() / 2);
+
+        let lower_block = self.alloc_next_block();
+        let upper_block = self.alloc_next_block();
+
+        let below = self.add_case_comparison(IRBranchCondition::LessThan, value, upper[0].0);
+        self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: below, src2: IRValue::Immediate(Value::code_constant(0)), dest_true: lower_block, dest_false: upper_block });
+
+        self.current_block = lower_block;
+        self.add_case_search(value, lower, default);
+
+        self.current_block = upper_block;
+        self.add_case_search(value, upper, default);
+    }
+
+    /// Jump through a table with an entry for every value from the smallest case to the largest, after sending values
+    /// outside of that range to the default block
+    fn add_case_jump_table(&mut self, value: &IRValue, cases: &[(i64, usize)], default: usize, entries: usize)
+    {
+        let (first, last) = (cases[0].0, cases[cases.len() - 1].0);
+
+        let below = self.add_case_comparison(IRBranchCondition::LessThan, value, first);
+        let upper_check = self.alloc_next_block();
+        self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: below, src2: IRValue::Immediate(Value::code_constant(0)), dest_true: default, dest_false: upper_check });
+
+        self.current_block = upper_check;
+        let above = self.add_case_comparison(IRBranchCondition::GreaterThan, value, last);
+        let lookup = self.alloc_next_block();
+        self.mut_current_block().add_instruction(IRInstruction::Branch { condition: IRBranchCondition::NotEqual, src1: above, src2: IRValue::Immediate(Value::code_constant(0)), dest_true: default, dest_false: lookup });
+
+        // Each entry is the address of a block, so the index is scaled by the size of a pointer
+        self.current_block = lookup;
+
+        let first = self.case_operand(first);
+        let index = IRValue::Register(self.alloc_next_register());
+        self.mut_current_block().add_instruction(IRInstruction::Sub { dest: index.clone(), src1: value.clone(), src2: first });
+
+        let offset = IRValue::Register(self.alloc_next_register());
+        let shift = self.pointer_size.trailing_zeros();
+        self.mut_current_block().add_instruction(IRInstruction::ShiftLeft { dest: offset.clone(), src1: index, src2: IRValue::Immediate(Value::code_constant(shift)) });
+
+        let mut targets = vec![default; entries];
+
+        for (case_value, block) in cases
+        {
+            targets[case_value.wrapping_sub(cases[0].0) as usize] = *block;
+        }
+
+        self.mut_current_block().add_instruction(IRInstruction::JumpTable { offset, targets });
+    }
+
+    /// Compare a value with a case value, giving a register which is nonzero if the condition holds
+    fn add_case_comparison(&mut self, condition: IRBranchCondition, value: &IRValue, case_value: i64) -> IRValue
+    {
+        let case_value = self.case_operand(case_value);
+        let dest = IRValue::Register(self.alloc_next_register());
+
+        self.mut_current_block().add_instruction(IRInstruction::Conditional { condition, dest: dest.clone(), src1: value.clone(), src2: case_value });
+
+        dest
+    }
+
+    /// Give a case value as an operand, moving it into a register if it is too large for an instruction's immediate
+    fn case_operand(&mut self, case_value: i64) -> IRValue
+    {
+        let immediate = IRValue::Immediate(Value::integer_constant(case_value as u64, RawValueType::I64));
+
+        // Comparisons may add one to their immediate, so the largest twelve bit value is left out
+        if (-2048..2047).contains(&case_value)
+        {
+            immediate
+        }
+        else
+        {
+            self.value_in_register(immediate)
+        }
+    }
+}
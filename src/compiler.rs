@@ -7,7 +7,15 @@ use super::parser;
 use super::preprocessor;
 use super::tokenizer::*;
 use super::errors::*;
-use super::settings::CompilerSettings;
+use super::settings::{CompilerSettings, GraphFormat};
+
+/// Name of an output file made from a source file, which is written to the current directory
+fn output_filename(filename: &str, extension: &str) -> String
+{
+    let stem = Path::new(filename).file_name().unwrap().to_str().unwrap().split('.').next().unwrap();
+
+    format!("{}.{}", stem, extension)
+}
 
 pub struct Compiler<'a>
 {
@@ -28,6 +36,12 @@ impl<'a> Compiler<'a>
         }
     }
 
+    /// Get the settings the compiler was started with
+    pub fn settings(&self) -> &'a CompilerSettings
+    {
+        self.settings
+    }
+
     /// Ensure that a particular file is loaded
     pub fn load_file(&mut self, filename: &str) -> CompilerResult<()>
     {
@@ -44,6 +58,13 @@ impl<'a> Compiler<'a>
         Ok(())
     }
 
+    /// Add a file whose text is held in memory rather than read from disk
+    #[cfg(test)]
+    pub fn add_file(&mut self, manager: FileManager)
+    {
+        self.loaded_files.entry(manager.filename.clone()).or_insert(manager);
+    }
+
     /// Get the file manager for a file or return an error if it can't be read
     pub fn get_file_manager(&mut self, filename: &str) -> CompilerResult<&mut FileManager>
     {
@@ -66,10 +87,73 @@ impl<'a> Compiler<'a>
         // Open the file, or return a BadFilename error
         self.get_file_manager(filename)?;
 
+        let settings = self.settings;
+
         // Pass the file on to the preprocessor
         let mut preprocessor_context = preprocessor::PreprocessorContext::with_compiler(self);
+        preprocessor_context.define_predefined_macros()?;
+        preprocessor_context.define_command_line_macros()?;
         let tokens = preprocessor_context.preprocess(filename)?;
 
+        if settings.include_graph == Some(GraphFormat::Dot)
+        {
+            print!("{}", preprocessor_context.include_graph_dot(filename));
+        }
+
+        // Make rules name the assembly output as their target unless given others
+        let dependency_targets = match settings.dependency_targets.is_empty()
+        {
+            true => vec![output_filename(filename, "s")],
+            false => settings.dependency_targets.clone()
+        };
+
+        // -M and -MM replace compilation, while -MD and -MMD write the rule to a file alongside it
+        if settings.dependencies || settings.user_dependencies || settings.write_dependencies || settings.write_user_dependencies
+        {
+            let system = settings.dependencies || settings.write_dependencies;
+            let rule = preprocessor_context.make_rule(filename, &dependency_targets, system, settings.phony_dependencies);
+
+            let output = match &settings.dependency_file
+            {
+                Some(output) => Some(output.clone()),
+                None if settings.dependencies || settings.user_dependencies => None,
+                None => Some(output_filename(filename, "d"))
+            };
+
+            match output
+            {
+                Some(output) => std::fs::write(&output, rule).map_err(|_| CompilerError::WriteFailed(output))?,
+                None => print!("{}", rule)
+            }
+
+            if settings.dependencies || settings.user_dependencies
+            {
+                return Ok(());
+            }
+        }
+
+        // Preprocessing only writes the preprocessed source, or the macros which were defined, to standard output
+        if settings.dump_macros
+        {
+            print!("{}", preprocessor_context.macro_definitions());
+            return Ok(());
+        }
+
+        if settings.preprocess_only
+        {
+            print!("{}", preprocessor_context.preprocessed_source(&tokens));
+            return Ok(());
+        }
+
+        // Anything which couldn't be lexed is only an error if it wasn't skipped or consumed by a directive
+        check_other_tokens(&tokens)?;
+
+        // Adjacent string literals are merged once preprocessing is complete
+        let tokens = concatenate_string_literals(tokens)?;
+
+        // Reserved words only become keywords after preprocessing, so macros may still be named after them
+        let tokens = identify_keywords(tokens, self.settings.standard);
+
         // Display the preprocessed tokens if requested
         if self.settings.dump_tokens
         {
@@ -91,7 +175,7 @@ impl<'a> Compiler<'a>
         }
 
         // Convert to intermediate representation
-        let ir = codegen::parse_tree_to_ir(tree)?;
+        let ir = codegen::parse_tree_to_ir(tree, self.settings.target)?;
 
         // Display the intermediate representation if requested
         if self.settings.dump_intermediate_representation
@@ -100,7 +184,7 @@ impl<'a> Compiler<'a>
         }
 
         // Produce assembly from the intermediate representation
-        let asm_generator = codegen::AssemblyCodeGenerator::from_ir(ir);
+        let mut asm_generator = codegen::AssemblyCodeGenerator::from_ir(ir);
         let asm = asm_generator.codegen()?;
 
         // Display the assembly output if requested
@@ -112,8 +196,7 @@ impl<'a> Compiler<'a>
         // Finally, we get the name of the output file, and write the generated assembly output if the no-out-file flag is not set
         if !self.settings.supress_output
         {
-            let filename = Path::new(&filename).file_name().unwrap().to_str().unwrap().split(".").nth(0).unwrap();
-            let mut f = std::fs::File::create(format!("{}.s", filename)).unwrap();
+            let mut f = std::fs::File::create(output_filename(filename, "s")).unwrap();
 
             f.write_all(asm.as_bytes()).unwrap();
         }
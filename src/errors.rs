@@ -1,9 +1,15 @@
 #![allow(dead_code)]
 
-use crate::{preprocessor::PreprocessorError, compiler::Compiler, parser::ParseError, codegen::CodegenError};
+use crate::{tokenizer::{TokenizerError, ExpansionFrame}, preprocessor::PreprocessorError, compiler::Compiler, parser::ParseError, codegen::CodegenError};
 pub enum CompilerError
 {
     BadFilename(String),
+    /// An output file which couldn't be written
+    WriteFailed(String),
+    /// A function which needs more registers at once than the code generator can give it, as nothing is spilled
+    OutOfRegisters(String),
+    /// Every lexical error found in a file, in the order they appear
+    TokenizerError(Vec<TokenizerError>),
     PreprocessorError(PreprocessorError),
     ParseError(ParseError),
     CodegenError(CodegenError)
@@ -16,6 +22,9 @@ impl std::fmt::Display for CompilerError
         match self
         {
             CompilerError::BadFilename(name) => write!(f, "Unable to open file {}", name),
+            CompilerError::WriteFailed(name) => write!(f, "Unable to write file {}", name),
+            CompilerError::OutOfRegisters(name) => write!(f, "Function {} needs more registers than are available", name),
+            CompilerError::TokenizerError(errors) => write!(f, "Tokenizer error: {}", errors[0]),
             CompilerError::PreprocessorError(error) => write!(f, "Preprocessor error: {}", error),
             CompilerError::ParseError(error) => write!(f, "Parse error: {}", error),
             CompilerError::CodegenError(error) => write!(f, "Codegen error: {}", error)
@@ -23,6 +32,14 @@ impl std::fmt::Display for CompilerError
     }
 }
 
+impl std::convert::From<TokenizerError> for CompilerError
+{
+    fn from(error: TokenizerError) -> Self
+    {
+        CompilerError::TokenizerError(vec![error])
+    }
+}
+
 impl std::convert::From<PreprocessorError> for CompilerError
 {
     fn from(error: PreprocessorError) -> Self
@@ -47,32 +64,72 @@ impl std::convert::From<CodegenError> for CompilerError
     }
 }
 
+/// Show each macro expansion an error happened in, from the innermost out to where the first macro was invoked
+fn display_expansions(compiler: &mut Compiler, expansions: &[ExpansionFrame])
+{
+    for frame in expansions
+    {
+        match &frame.definition
+        {
+            Some(definition) => eprintln!("note: expanded from macro '{}' defined at {}, invoked at {}", frame.name, definition, frame.invocation),
+            None => eprintln!("note: expanded from predefined macro '{}' invoked at {}", frame.name, frame.invocation)
+        }
+
+        if let Ok(file) = compiler.get_file_manager(&frame.invocation.filename)
+        {
+            file.display_arrow(&frame.invocation);
+        }
+    }
+}
+
 impl CompilerError
 {
+    /// Show where the error occurred, tokenizer errors after the first are reported here in full
     pub fn output_more(self, compiler: &mut Compiler)
     {
         match self
         {
+            CompilerError::TokenizerError(errors) =>
+            {
+                for (i, error) in errors.iter().enumerate()
+                {
+                    if i > 0
+                    {
+                        eprintln!("Tokenizer error: {}", error);
+                    }
+
+                    if let Ok(file) = compiler.get_file_manager(&error.location.filename)
+                    {
+                        file.display_arrow(&error.location);
+                    }
+                }
+            },
             CompilerError::PreprocessorError(error) =>
             {
                 if let Ok(file) = compiler.get_file_manager(&error.location.filename)
                 {
-                    file.display_arrow(&error.location, error.arrow_length);
+                    file.display_arrow(&error.location);
                 }
+
+                display_expansions(compiler, &error.expansions);
             },
             CompilerError::ParseError(error) =>
             {
                 if let Ok(file) = compiler.get_file_manager(&error.location.filename)
                 {
-                    file.display_arrow(&error.location, error.arrow_length);
+                    file.display_arrow(&error.location);
                 }
+
+                display_expansions(compiler, &error.expansions);
             },
             CompilerError::CodegenError(error) =>
             {
                 if let Ok(file) = compiler.get_file_manager(&error.location.filename)
                 {
-                    file.display_arrow(&error.location, error.arrow_length);
+                    file.display_arrow(&error.location);
                 }
+
+                display_expansions(compiler, &error.expansions);
             },
             _ => {}
         }
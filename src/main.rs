@@ -16,8 +16,7 @@ fn main()
     let env = Env::default().filter_or("QOR_CC_LOG", "trace");
     env_logger::init_from_env(env);
 
-    use clap::Parser;
-    let compiler_settings = settings::CompilerSettings::parse();
+    let compiler_settings = settings::CompilerSettings::from_command_line();
 
     let mut compiler_instance = compiler::Compiler::with_settings(&compiler_settings);
 
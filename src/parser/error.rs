@@ -7,8 +7,9 @@ pub struct ParseError
 {
     pub error: ParseErrorType,
     pub location: Location,
-    pub original_location: Option<Location>,
-    pub arrow_length: usize
+    pub original_location: Option<Box<Location>>,
+    /// Macro expansions the error happened in, innermost first
+    pub expansions: Vec<ExpansionFrame>
 }
 
 pub enum ParseErrorType
@@ -44,8 +45,8 @@ impl ParseError
         {
             location: token.location.clone(),
             error: ParseErrorType::SyntaxError(error),
-            original_location: token.original_location.clone(),
-            arrow_length: token.code_styled().len(),
+            original_location: token.original_location.clone().map(Box::new),
+            expansions: token.expansions.clone(),
         }
     }
 
@@ -80,6 +81,34 @@ impl ParseError
         }
     }
 
+    pub fn expect_floating_literal(token: Option<&Token>) -> Result<Token, ParseError>
+    {
+        let token = ParseError::prevent_eof(token)?;
+
+        if let TokenType::FloatingLiteral(_) = &token.token_type
+        {
+            Ok(token)
+        }
+        else
+        {
+            Err(ParseError::syntax_error(format!("Expected floating literal, got {}", token.code_styled()), &token))
+        }
+    }
+
+    pub fn expect_character_literal(token: Option<&Token>) -> Result<Token, ParseError>
+    {
+        let token = ParseError::prevent_eof(token)?;
+
+        if let TokenType::CharacterLiteral(_) = &token.token_type
+        {
+            Ok(token)
+        }
+        else
+        {
+            Err(ParseError::syntax_error(format!("Expected character literal, got {}", token.code_styled()), &token))
+        }
+    }
+
     pub fn expect_identifier(token: Option<&Token>) -> Result<Token, ParseError>
     {
         let token = ParseError::prevent_eof(token)?;
@@ -88,23 +117,27 @@ impl ParseError
         {
             Ok(token)
         }
+        else if let TokenType::Keyword(keyword) = &token.token_type
+        {
+            Err(ParseError::syntax_error(format!("Expected identifier, got reserved word {}", keyword), &token))
+        }
         else
         {
             Err(ParseError::syntax_error(format!("Expected identifier, got {}", token.code_styled()), &token))
         }
     }
 
-    pub fn expect_specific_identifier(token: Option<&Token>, specific: &str) -> Result<Token, ParseError>
+    pub fn expect_keyword(token: Option<&Token>, keyword: Keyword) -> Result<Token, ParseError>
     {
         let token = ParseError::prevent_eof(token)?;
 
-        if TokenType::Identifier(specific.to_string()) == token.token_type
+        if token.is_keyword(keyword)
         {
             Ok(token)
         }
         else
         {
-            Err(ParseError::syntax_error(format!("Expected {}, got {}", specific, token.code_styled()), &token))
+            Err(ParseError::syntax_error(format!("Expected {}, got {}", keyword, token.code_styled()), &token))
         }
     }
 
@@ -116,6 +149,10 @@ impl ParseError
         {
             Ok(token)
         }
+        else if let TokenType::Keyword(keyword) = &token.token_type
+        {
+            Err(ParseError::syntax_error(format!("Expected {}, got reserved word {}", name, keyword), &token))
+        }
         else
         {
             Err(ParseError::syntax_error(format!("Expected {}, got {}", name, token.code_styled()), &token))
@@ -136,18 +173,17 @@ impl ParseError
         }
     }
 
-    pub fn expect_symbol(token: Option<&Token>, symbol: &str) -> Result<(), ParseError>
+    pub fn expect_punct(token: Option<&Token>, punct: Punct) -> Result<(), ParseError>
     {
         let token = ParseError::prevent_eof(token)?;
 
-        if let TokenType::Symbol(token_symbol) = &token.token_type
+        if token.is_punct(punct)
         {
-            if token_symbol == symbol
-            {
-                return Ok(());
-            }
+            Ok(())
+        }
+        else
+        {
+            Err(ParseError::syntax_error(format!("Expected {}, got {}", punct, token.code_styled()), &token))
         }
-
-        Err(ParseError::syntax_error(format!("Expected {}, got {}", symbol, token.code_styled()), &token))
     }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::tokenizer::{Token, TokenType};
+use crate::tokenizer::{Token, TokenType, Punct};
 use crate::errors::CompilerResult;
 
 use super::*;
@@ -10,13 +10,13 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
     {
         let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
-        if peeked_next.token_type == TokenType::Symbol("(".to_string())
+        if peeked_next.token_type == TokenType::Punct(Punct::LeftParen)
         {
             self.stream.next();
 
             let result = self.parse_expression()?;
 
-            ParseError::expect_symbol(self.stream.next(), ")")?;
+            ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
 
             return Ok(result)
         }
@@ -25,6 +25,8 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
         {
             TokenType::Identifier(_) => self.parse_identifier("variable name"),
             TokenType::IntegerLiteral(_) => self.parse_integer_value(),
+            TokenType::FloatingLiteral(_) => self.parse_floating_value(),
+            TokenType::CharacterLiteral(_) => self.parse_character_value(),
             _ => Err(ParseError::syntax_error(format!("Expected primary expression, got {}", peeked_next.code_styled()), &peeked_next).into())
         }
     }
@@ -43,15 +45,15 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "[" => PostfixExpressionOperation::ArrayIndexing,
-                    "(" => PostfixExpressionOperation::FunctionCall,
-                    "." => PostfixExpressionOperation::MemberAccess,
-                    "->" => PostfixExpressionOperation::IndirectMemberAccess,
-                    "++" => PostfixExpressionOperation::Increment,
-                    "--" => PostfixExpressionOperation::Decrement,
+                    Punct::LeftBracket => PostfixExpressionOperation::ArrayIndexing,
+                    Punct::LeftParen => PostfixExpressionOperation::FunctionCall,
+                    Punct::Period => PostfixExpressionOperation::MemberAccess,
+                    Punct::Arrow => PostfixExpressionOperation::IndirectMemberAccess,
+                    Punct::Increment => PostfixExpressionOperation::Increment,
+                    Punct::Decrement => PostfixExpressionOperation::Decrement,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -69,23 +71,23 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
                 PostfixExpressionOperation::ArrayIndexing => 
                 {
                     children.push(self.parse_expression()?);
-                    ParseError::expect_symbol(self.stream.next(), "]")?;
+                    ParseError::expect_punct(self.stream.next(), Punct::RightBracket)?;
                 },
                 PostfixExpressionOperation::FunctionCall => 
                 {
-                    while self.stream.peek().map(|v| &v.token_type) != Some(&TokenType::Symbol(")".to_string()))
+                    while self.stream.peek().map(|v| &v.token_type) != Some(&TokenType::Punct(Punct::RightParen))
                     {
                         children.push(self.parse_expression()?);
 
-                        if self.stream.peek().map(|v| &v.token_type) != Some(&TokenType::Symbol(",".to_string()))
+                        if self.stream.peek().map(|v| &v.token_type) != Some(&TokenType::Punct(Punct::Comma))
                         {
                             break;
                         }
 
-                        ParseError::expect_symbol(self.stream.next(), ",")?;
+                        ParseError::expect_punct(self.stream.next(), Punct::Comma)?;
                     }
 
-                    ParseError::expect_symbol(self.stream.next(), ")")?;
+                    ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
                 },
                 PostfixExpressionOperation::MemberAccess => 
                 {
@@ -111,18 +113,19 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
         // Check for the operation
         let operation = match &peeked_next.token_type
         {
-            TokenType::Symbol(symbol_data) => 
+            TokenType::Punct(punct) => 
             {
-                match symbol_data.as_str()
+                match punct
                 {
-                    "-" => UnaryExpressionOperation::Negation,
-                    "+" => UnaryExpressionOperation::Positive,
-                    "--" => UnaryExpressionOperation::Decrement,
-                    "++" => UnaryExpressionOperation::Increment,
-                    "&" => UnaryExpressionOperation::Reference,
-                    "*" => UnaryExpressionOperation::Dereference,
-                    "~" => UnaryExpressionOperation::BitwiseNot,
-                    "!" => UnaryExpressionOperation::LogicalNot,
+                    Punct::Minus => UnaryExpressionOperation::Negation,
+                    Punct::Plus => UnaryExpressionOperation::Positive,
+                    Punct::Decrement => UnaryExpressionOperation::Decrement,
+                    Punct::Increment => UnaryExpressionOperation::Increment,
+                    Punct::Ampersand => UnaryExpressionOperation::Reference,
+                    Punct::Asterisk => UnaryExpressionOperation::Dereference,
+                    Punct::Tilde => UnaryExpressionOperation::BitwiseNot,
+                    Punct::Exclamation => UnaryExpressionOperation::LogicalNot,
+                    Punct::LogicalAnd => { return self.parse_label_address(); },
                     _ => { return self.parse_postfix_expression(); }
                 }
             }
@@ -138,6 +141,16 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
         Ok(ParseTreeNode::UnaryExpression { operation, child: Box::new(inner), optoken })
     }
 
+    /// Parse the address of a label, which is a GNU extension written `&&label`
+    pub fn parse_label_address(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        ParseError::expect_punct(self.stream.next(), Punct::LogicalAnd)?;
+
+        let token = ParseError::expect_named_identifier(self.stream.next(), "label name")?;
+
+        Ok(ParseTreeNode::LabelAddressExpression { name: token.code_styled(), token })
+    }
+
     /// Parse a cast expression
     pub fn parse_cast_expression(&mut self) -> CompilerResult<ParseTreeNode>
     {
@@ -158,12 +171,12 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "*" => MultiplicativeExpressionOperation::Multiplication,
-                    "/" => MultiplicativeExpressionOperation::Division,
-                    "%" => MultiplicativeExpressionOperation::Modulus,
+                    Punct::Asterisk => MultiplicativeExpressionOperation::Multiplication,
+                    Punct::Slash => MultiplicativeExpressionOperation::Division,
+                    Punct::Percent => MultiplicativeExpressionOperation::Modulus,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -193,11 +206,11 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "+" => AdditiveExpressionOperation::Addition,
-                    "-" => AdditiveExpressionOperation::Subtraction,
+                    Punct::Plus => AdditiveExpressionOperation::Addition,
+                    Punct::Minus => AdditiveExpressionOperation::Subtraction,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -227,11 +240,11 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "<<" => ShiftExpressionOperation::ShiftLeft,
-                    ">>" => ShiftExpressionOperation::ShiftRight,
+                    Punct::ShiftLeft => ShiftExpressionOperation::ShiftLeft,
+                    Punct::ShiftRight => ShiftExpressionOperation::ShiftRight,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -261,13 +274,13 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "<" => RelationalExpressionOperation::LessThan,
-                    "<=" => RelationalExpressionOperation::LessThanOrEqual,
-                    ">" => RelationalExpressionOperation::GreaterThan,
-                    ">=" => RelationalExpressionOperation::GreaterThanOrEqual,
+                    Punct::LessThan => RelationalExpressionOperation::LessThan,
+                    Punct::LessThanEqual => RelationalExpressionOperation::LessThanOrEqual,
+                    Punct::GreaterThan => RelationalExpressionOperation::GreaterThan,
+                    Punct::GreaterThanEqual => RelationalExpressionOperation::GreaterThanOrEqual,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -297,11 +310,11 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             // Get the operation
             let operation = match peeked_next.token_type
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
+                TokenType::Punct(punct) =>
+                match punct
                 {
-                    "==" => EqualityExpressionOperation::Equality,
-                    "!=" => EqualityExpressionOperation::Nonequality,
+                    Punct::Equal => EqualityExpressionOperation::Equality,
+                    Punct::NotEqual => EqualityExpressionOperation::Nonequality,
                     _ => { return Ok( first ) }
                 }
                 _ => { return Ok( first ) }
@@ -329,7 +342,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol("&".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::Ampersand)
             {
                 return Ok(first);
             };
@@ -356,7 +369,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol("^".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::Caret)
             {
                 return Ok(first);
             };
@@ -383,7 +396,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol("|".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::Pipe)
             {
                 return Ok(first);
             };
@@ -410,7 +423,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol("&&".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::LogicalAnd)
             {
                 return Ok(first);
             };
@@ -437,7 +450,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol("||".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::LogicalOr)
             {
                 return Ok(first);
             };
@@ -462,7 +475,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
         let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
         // Get the operation
-        if peeked_next.token_type != TokenType::Symbol("?".to_string())
+        if peeked_next.token_type != TokenType::Punct(Punct::Question)
         {
             return Ok(first);
         };
@@ -473,7 +486,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
         // Get the second part of the expression
         let second = self.parse_expression()?;
 
-        ParseError::expect_symbol(self.stream.next(), ":")?;
+        ParseError::expect_punct(self.stream.next(), Punct::Colon)?;
 
         let third = self.parse_expression()?;
 
@@ -484,43 +497,40 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
     pub fn parse_assignment_expression(&mut self) -> CompilerResult<ParseTreeNode>
     {
         // Get the first part of the expression
-        let mut first = self.parse_conditional_expression()?;
+        let first = self.parse_conditional_expression()?;
 
-        loop
-        {
-            // Peek the operation
-            let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
+        // Peek the operation
+        let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
-            // Get the operation
-            let operation = match peeked_next.token_type
+        // Get the operation
+        let operation = match peeked_next.token_type
+        {
+            TokenType::Punct(punct) =>
+            match punct
             {
-                TokenType::Symbol(symbol_text) =>
-                match symbol_text.as_str()
-                {
-                    "=" => AssignmentExpressionOperation::Assignment,
-                    "*=" => AssignmentExpressionOperation::MultiplicationAssignment,
-                    "/=" => AssignmentExpressionOperation::DivisionAssignment,
-                    "%=" => AssignmentExpressionOperation::ModulusAssignment,
-                    "+=" => AssignmentExpressionOperation::AdditionAssignment,
-                    "-=" => AssignmentExpressionOperation::SubtractionAssignment,
-                    "<<=" => AssignmentExpressionOperation::ShiftLeftAssignment,
-                    ">>=" => AssignmentExpressionOperation::ShiftRightAssignment,
-                    "&=" => AssignmentExpressionOperation::AndAssignment,
-                    "^=" => AssignmentExpressionOperation::XorAssignment,
-                    "|=" => AssignmentExpressionOperation::OrAssignment,
-                    _ => { return Ok( first ) }
-                }
+                Punct::Assign => AssignmentExpressionOperation::Assignment,
+                Punct::MultiplyAssign => AssignmentExpressionOperation::MultiplicationAssignment,
+                Punct::DivideAssign => AssignmentExpressionOperation::DivisionAssignment,
+                Punct::ModulusAssign => AssignmentExpressionOperation::ModulusAssignment,
+                Punct::AddAssign => AssignmentExpressionOperation::AdditionAssignment,
+                Punct::SubtractAssign => AssignmentExpressionOperation::SubtractionAssignment,
+                Punct::ShiftLeftAssign => AssignmentExpressionOperation::ShiftLeftAssignment,
+                Punct::ShiftRightAssign => AssignmentExpressionOperation::ShiftRightAssignment,
+                Punct::AndAssign => AssignmentExpressionOperation::AndAssignment,
+                Punct::XorAssign => AssignmentExpressionOperation::XorAssignment,
+                Punct::OrAssign => AssignmentExpressionOperation::OrAssignment,
                 _ => { return Ok( first ) }
-            };
+            }
+            _ => { return Ok( first ) }
+        };
 
-            // Get the operation token
-            let optoken = ParseError::prevent_eof(self.stream.next())?;
+        // Get the operation token
+        let optoken = ParseError::prevent_eof(self.stream.next())?;
 
-            // Get the second part of the expression
-            let second = self.parse_conditional_expression()?;
+        // Get the second part of the expression, which may itself be an assignment as they group right to left
+        let second = self.parse_assignment_expression()?;
 
-            first = ParseTreeNode::AssignmentExpression { operation, children: vec![first, second], optoken };
-        }
+        Ok(ParseTreeNode::AssignmentExpression { operation, children: vec![first, second], optoken })
     }
 
     /// Parse a comma expression
@@ -535,7 +545,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
             let peeked_next = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
             // Get the operation
-            if peeked_next.token_type != TokenType::Symbol(",".to_string())
+            if peeked_next.token_type != TokenType::Punct(Punct::Comma)
             {
                 return Ok(first);
             };
@@ -1,4 +1,4 @@
-use crate::tokenizer::{Token, TokenType};
+use crate::tokenizer::{Token, TokenType, Keyword, Punct, IntegerSuffix, FloatingSuffix, EncodingPrefix};
 use crate::errors::CompilerResult;
 
 use super::*;
@@ -63,26 +63,26 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
 
         let name = ParseError::expect_named_identifier(self.stream.next(), "function name")?;
 
-        ParseError::expect_symbol(self.stream.next(), "(")?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
 
         let mut arguments = Vec::new();
 
-        while Some(&TokenType::Symbol(String::from(")"))) != self.stream.peek().map(|v| &v.token_type)
+        while Some(&TokenType::Punct(Punct::RightParen)) != self.stream.peek().map(|v| &v.token_type)
         {
             let arg_type = self.parse_type()?;
             let arg_name = ParseError::expect_named_identifier(self.stream.next(), "argument name")?;
 
             arguments.push((arg_name.code_styled(), arg_type, arg_name));
 
-            if Some(&TokenType::Symbol(String::from(","))) != self.stream.peek().map(|v| &v.token_type)
+            if Some(&TokenType::Punct(Punct::Comma)) != self.stream.peek().map(|v| &v.token_type)
             {
                 break;
             }
             
-            ParseError::expect_symbol(self.stream.next(), ",")?;
+            ParseError::expect_punct(self.stream.next(), Punct::Comma)?;
         }
 
-        ParseError::expect_symbol(self.stream.next(), ")")?;
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
         
         let statement = self.parse_statement()?;
 
@@ -92,18 +92,18 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
     /// Parse an if statement
     pub fn parse_if_statement(&mut self) -> CompilerResult<ParseTreeNode>
     {
-        ParseError::expect_specific_identifier(self.stream.next(), "if")?;
-        ParseError::expect_symbol(self.stream.next(), "(")?;
+        ParseError::expect_keyword(self.stream.next(), Keyword::If)?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
 
         let expr = self.parse_expression()?;
         
-        ParseError::expect_symbol(self.stream.next(), ")")?;
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
 
         let statement = self.parse_statement()?;
 
-        if TokenType::Identifier("else".to_string()) == ParseError::prevent_eof(self.stream.peek().map(|v| *v))?.token_type
+        if TokenType::Keyword(Keyword::Else) == ParseError::prevent_eof(self.stream.peek().map(|v| *v))?.token_type
         {
-            ParseError::expect_specific_identifier(self.stream.next(), "else")?;
+            ParseError::expect_keyword(self.stream.next(), Keyword::Else)?;
 
             Ok(ParseTreeNode::IfStatement { children: vec![expr, statement, self.parse_statement()?] })
         }
@@ -116,87 +116,390 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
     /// Parse a while loop
     pub fn parse_while_loop(&mut self) -> CompilerResult<ParseTreeNode>
     {
-        ParseError::expect_specific_identifier(self.stream.next(), "while")?;
-        ParseError::expect_symbol(self.stream.next(), "(")?;
+        ParseError::expect_keyword(self.stream.next(), Keyword::While)?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
 
         let expr = self.parse_expression()?;
         
-        ParseError::expect_symbol(self.stream.next(), ")")?;
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
 
         let statement = self.parse_statement()?;
 
         Ok(ParseTreeNode::WhileLoop { children: vec![expr, statement] })
     }
 
+    /// Parse a switch statement
+    pub fn parse_switch_statement(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        let token = ParseError::expect_keyword(self.stream.next(), Keyword::Switch)?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
+
+        let expr = self.parse_expression()?;
+
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
+
+        let statement = self.parse_statement()?;
+
+        Ok(ParseTreeNode::SwitchStatement { children: vec![expr, statement], token })
+    }
+
+    /// Parse a case or default label, along with the statement it labels
+    pub fn parse_case_label(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        let token = ParseError::prevent_eof(self.stream.next())?;
+
+        if token.token_type == TokenType::Keyword(Keyword::Default)
+        {
+            ParseError::expect_punct(self.stream.next(), Punct::Colon)?;
+
+            return Ok(ParseTreeNode::DefaultStatement { child: Box::new(self.parse_statement()?), token });
+        }
+
+        // Case values are constant expressions, which can't contain assignments or commas
+        let value = self.parse_conditional_expression()?;
+
+        ParseError::expect_punct(self.stream.next(), Punct::Colon)?;
+
+        Ok(ParseTreeNode::CaseStatement { children: vec![value, self.parse_statement()?], token })
+    }
+
+    /// Parse a do while loop
+    pub fn parse_do_while_loop(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        ParseError::expect_keyword(self.stream.next(), Keyword::Do)?;
+
+        let statement = self.parse_statement()?;
+
+        ParseError::expect_keyword(self.stream.next(), Keyword::While)?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
+
+        let expr = self.parse_expression()?;
+
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
+        ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+        Ok(ParseTreeNode::DoWhileLoop { children: vec![statement, expr] })
+    }
+
+    /// Parse a for loop, whose first clause may be a declaration
+    pub fn parse_for_loop(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        ParseError::expect_keyword(self.stream.next(), Keyword::For)?;
+        ParseError::expect_punct(self.stream.next(), Punct::LeftParen)?;
+
+        // A declaration consumes its own semicolon, so the other forms of the first clause are parsed as statements
+        let init = if self.peek_type()
+        {
+            self.parse_declaration()?
+        }
+        else
+        {
+            let child = match self.stream.next_if(|token| token.is_punct(Punct::Semicolon))
+            {
+                Some(_) => None,
+                None =>
+                {
+                    let expression = self.parse_expression()?;
+                    ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+                    Some(Box::new(expression))
+                }
+            };
+
+            ParseTreeNode::ExpressionStatement { child }
+        };
+
+        // The condition and step may both be left out
+        let condition = if ParseError::prevent_eof(self.stream.peek().copied())?.is_punct(Punct::Semicolon)
+        {
+            None
+        }
+        else
+        {
+            Some(Box::new(self.parse_expression()?))
+        };
+
+        ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+        let step = if ParseError::prevent_eof(self.stream.peek().copied())?.is_punct(Punct::RightParen)
+        {
+            None
+        }
+        else
+        {
+            Some(Box::new(self.parse_expression()?))
+        };
+
+        ParseError::expect_punct(self.stream.next(), Punct::RightParen)?;
+
+        let statement = self.parse_statement()?;
+
+        Ok(ParseTreeNode::ForLoop { init: Box::new(init), condition, step, child: Box::new(statement) })
+    }
+
     /// Parse a statement
     pub fn parse_statement(&mut self) -> CompilerResult<ParseTreeNode>
     {
         let peeked = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
         // Compound statement
-        if peeked.token_type == TokenType::Symbol(String::from("{"))
+        if peeked.token_type == TokenType::Punct(Punct::LeftBrace)
         {
             let mut children = Vec::new();
 
-            ParseError::expect_symbol(self.stream.next(), "{")?;
+            ParseError::expect_punct(self.stream.next(), Punct::LeftBrace)?;
 
             loop
             {
                 let peeked = ParseError::prevent_eof(self.stream.peek().map(|v| *v))?;
 
-                if peeked.token_type == TokenType::Symbol(String::from("}"))
+                if peeked.token_type == TokenType::Punct(Punct::RightBrace)
                 {
                     break;
                 }
 
-                children.push(self.parse_statement()?);
+                // Declarations may only appear directly inside a block
+                if self.peek_type()
+                {
+                    children.push(self.parse_declaration()?);
+                }
+                else
+                {
+                    children.push(self.parse_statement()?);
+                }
             }
 
-            ParseError::expect_symbol(self.stream.next(), "}")?;
+            ParseError::expect_punct(self.stream.next(), Punct::RightBrace)?;
 
             Ok(ParseTreeNode::StatementBlock { children })
         }
         // Return statement
-        else if peeked.token_type == TokenType::Identifier(String::from("return"))
+        else if peeked.token_type == TokenType::Keyword(Keyword::Return)
         {
             self.stream.next();
 
             let value = self.parse_expression()?;
 
-            ParseError::expect_symbol(self.stream.next(), ";")?;
+            ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
 
             Ok(ParseTreeNode::ReturnStatement { child: Some(Box::new(value)) })
         }
         // If statement
-        else if peeked.token_type == TokenType::Identifier(String::from("if"))
+        else if peeked.token_type == TokenType::Keyword(Keyword::If)
         {
             self.parse_if_statement()
         }
         // While loop
-        else if peeked.token_type == TokenType::Identifier(String::from("while"))
+        else if peeked.token_type == TokenType::Keyword(Keyword::While)
         {
             self.parse_while_loop()
         }
+        // Switch statement
+        else if peeked.token_type == TokenType::Keyword(Keyword::Switch)
+        {
+            self.parse_switch_statement()
+        }
+        // Case and default labels
+        else if peeked.token_type == TokenType::Keyword(Keyword::Case) || peeked.token_type == TokenType::Keyword(Keyword::Default)
+        {
+            self.parse_case_label()
+        }
+        // Do while loop
+        else if peeked.token_type == TokenType::Keyword(Keyword::Do)
+        {
+            self.parse_do_while_loop()
+        }
+        // For loop
+        else if peeked.token_type == TokenType::Keyword(Keyword::For)
+        {
+            self.parse_for_loop()
+        }
+        // Break and continue statements
+        else if peeked.token_type == TokenType::Keyword(Keyword::Break) || peeked.token_type == TokenType::Keyword(Keyword::Continue)
+        {
+            let token = peeked.clone();
+            self.stream.next();
+
+            ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+            match token.token_type
+            {
+                TokenType::Keyword(Keyword::Break) => Ok(ParseTreeNode::BreakStatement { token }),
+                _ => Ok(ParseTreeNode::ContinueStatement { token })
+            }
+        }
+        // Goto statement, which may jump to a computed address as a GNU extension
+        else if peeked.token_type == TokenType::Keyword(Keyword::Goto)
+        {
+            let token = ParseError::expect_keyword(self.stream.next(), Keyword::Goto)?;
+
+            let statement = if self.stream.next_if(|token| token.is_punct(Punct::Asterisk)).is_some()
+            {
+                ParseTreeNode::ComputedGotoStatement { child: Box::new(self.parse_expression()?), token }
+            }
+            else
+            {
+                let name = ParseError::expect_named_identifier(self.stream.next(), "label name")?;
+
+                ParseTreeNode::GotoStatement { name: name.code_styled(), token: name }
+            };
+
+            ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+            Ok(statement)
+        }
+        // Empty statement
+        else if peeked.token_type == TokenType::Punct(Punct::Semicolon)
+        {
+            self.stream.next();
+
+            Ok(ParseTreeNode::ExpressionStatement { child: None })
+        }
+        // Expression statement, or a labeled statement if the expression is a lone name followed by a colon
         else
         {
-            Err(ParseError::syntax_error(format!("Expected statement, got {}", peeked.code_styled()), &peeked).into())
+            let expression = self.parse_expression()?;
+
+            if let ParseTreeNode::VariableExpression { name, token } = &expression
+            {
+                if self.stream.next_if(|token| token.is_punct(Punct::Colon)).is_some()
+                {
+                    return Ok(ParseTreeNode::LabeledStatement { name: name.clone(), child: Box::new(self.parse_statement()?), token: token.clone() });
+                }
+            }
+
+            ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+            Ok(ParseTreeNode::ExpressionStatement { child: Some(Box::new(expression)) })
         }
     }
 
+    /// Parse a declaration of one or more variables, each of which may have an initializer
+    pub fn parse_declaration(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        let base_type = self.parse_type()?;
+        let mut value_type = base_type.clone();
+        let mut children = Vec::new();
+
+        loop
+        {
+            let name_token = ParseError::expect_named_identifier(self.stream.next(), "variable name")?;
+
+            let initializer = match self.stream.next_if(|token| token.is_punct(Punct::Assign))
+            {
+                Some(_) => Some(Box::new(self.parse_assignment_expression()?)),
+                None => None
+            };
+
+            children.push(ParseTreeNode::Declarator { name: name_token.code_styled(), value_type, child: initializer, name_token });
+
+            if self.stream.next_if(|token| token.is_punct(Punct::Comma)).is_none()
+            {
+                break;
+            }
+
+            // Each declarator after the first has its own level of indirection
+            value_type = ValueType { references: self.parse_pointers(), value_type: base_type.value_type.clone() };
+        }
+
+        ParseError::expect_punct(self.stream.next(), Punct::Semicolon)?;
+
+        Ok(ParseTreeNode::Declaration { children })
+    }
+
     /// Parse an expression
     pub fn parse_expression(&mut self) -> CompilerResult<ParseTreeNode>
     {
         self.parse_comma_expression()
     }
 
-    /// Parse an integer value
+    /// Parse an integer value, giving it the first type in its suffix's list which can represent it
     pub fn parse_integer_value(&mut self) -> CompilerResult<ParseTreeNode>
     {
         let number_token = ParseError::expect_integer_literal(self.stream.next())?;
 
-        if let TokenType::IntegerLiteral(number) = number_token.token_type
+        if let TokenType::IntegerLiteral(constant) = &number_token.token_type
+        {
+            let candidates: &[RawValueType] = match (constant.suffix, constant.decimal)
+            {
+                (IntegerSuffix::None, true) => &[RawValueType::I32, RawValueType::I64],
+                (IntegerSuffix::None, false) => &[RawValueType::I32, RawValueType::U32, RawValueType::I64, RawValueType::U64],
+                (IntegerSuffix::Unsigned, _) => &[RawValueType::U32, RawValueType::U64],
+                (IntegerSuffix::Long, true) | (IntegerSuffix::LongLong, true) => &[RawValueType::I64],
+                (IntegerSuffix::Long, false) | (IntegerSuffix::LongLong, false) => &[RawValueType::I64, RawValueType::U64],
+                (IntegerSuffix::UnsignedLong, _) | (IntegerSuffix::UnsignedLongLong, _) => &[RawValueType::U64],
+            };
+
+            let value_type = match candidates.iter().find(|t| t.can_represent(constant.value))
+            {
+                Some(value_type) => value_type.clone(),
+                None =>
+                {
+                    // Decimal constants which are too large for any signed type are treated as unsigned, as gcc does
+                    warn!("Integer constant {} is so large that it is unsigned", constant.spelling);
+                    RawValueType::U64
+                }
+            };
+
+            Ok(ParseTreeNode::ConstantExpression{ value: Value::integer_constant(constant.value, value_type), token: number_token })
+        }
+        else
+        {
+            unreachable!()
+        }
+    }
+
+    /// Parse a floating value
+    pub fn parse_floating_value(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        let number_token = ParseError::expect_floating_literal(self.stream.next())?;
+
+        if let TokenType::FloatingLiteral(constant) = &number_token.token_type
         {
-            Ok(ParseTreeNode::ConstantExpression{ value: Value::code_constant(number as u32), token: number_token })
+            let value_type = match constant.suffix
+            {
+                FloatingSuffix::None => RawValueType::F64,
+                FloatingSuffix::Float => RawValueType::F32,
+                FloatingSuffix::LongDouble => RawValueType::F128,
+            };
+
+            Ok(ParseTreeNode::ConstantExpression{ value: Value::floating_constant(constant.value, value_type), token: number_token })
+        }
+        else
+        {
+            unreachable!()
+        }
+    }
+
+    /// Parse a character constant, multi-character constants are packed with the first character in the highest byte
+    pub fn parse_character_value(&mut self) -> CompilerResult<ParseTreeNode>
+    {
+        let character_token = ParseError::expect_character_literal(self.stream.next())?;
+
+        if let TokenType::CharacterLiteral(literal) = &character_token.token_type
+        {
+            if literal.units.is_empty()
+            {
+                return Err(ParseError::syntax_error("Empty character constant".to_string(), &character_token).into());
+            }
+
+            // Only constants without a prefix may hold more than one code unit
+            if literal.prefix == EncodingPrefix::None
+            {
+                let value = literal.units.iter().fold(0u32, |value, unit| (value << 8) | unit);
+
+                Ok(ParseTreeNode::ConstantExpression{ value: Value::code_constant(value), token: character_token })
+            }
+            else if literal.units.len() == 1
+            {
+                let value_type = RawValueType::character_type(literal.prefix);
+
+                Ok(ParseTreeNode::ConstantExpression{ value: Value::integer_constant(literal.units[0] as u64, value_type), token: character_token })
+            }
+            else
+            {
+                Err(ParseError::syntax_error(format!("Character constant {} does not fit in a single code unit", character_token.code_styled()), &character_token).into())
+            }
         }
         else
         {
@@ -222,43 +525,63 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
     /// Parse a base integer type
     pub fn parse_base_integer_type(&mut self) -> CompilerResult<RawValueType>
     {
-        // Integral types must be identifiers
-        let token = ParseError::expect_named_identifier(self.stream.next(), "integral type")?;
+        // Integral types must be keywords
+        let token = ParseError::prevent_eof(self.stream.next())?;
 
-        match token.code_styled().as_str()
+        match token.token_type
         {
-            "char" => Ok(RawValueType::I8),
-            "short" => Ok(RawValueType::I16),
-            "int" => Ok(RawValueType::I32),
-            "long" => Ok(RawValueType::I64),
+            TokenType::Keyword(Keyword::Char) => Ok(RawValueType::I8),
+            TokenType::Keyword(Keyword::Short) => Ok(RawValueType::I16),
+            TokenType::Keyword(Keyword::Int) => Ok(RawValueType::I32),
+            TokenType::Keyword(Keyword::Long) => Ok(RawValueType::I64),
             _ => Err(ParseError::syntax_error(format!("Expected integral type, got {}", token.code_styled()), &token).into())
         }
     }
 
+    /// Check if the next token starts a type, and so a declaration
+    pub fn peek_type(&mut self) -> bool
+    {
+        matches!(self.stream.peek().map(|token| &token.token_type), Some(TokenType::Keyword(Keyword::Struct | Keyword::Void |
+            Keyword::Char | Keyword::Short | Keyword::Int | Keyword::Long | Keyword::Unsigned | Keyword::Signed)))
+    }
+
+    /// Parse the asterisks of a pointer type, returning how many there were
+    pub fn parse_pointers(&mut self) -> usize
+    {
+        let mut reference_count = 0;
+
+        while self.stream.next_if(|token| token.is_punct(Punct::Asterisk)).is_some()
+        {
+            reference_count += 1;
+        }
+
+        reference_count
+    }
+
     /// Parse a type from the stream
     pub fn parse_type(&mut self) -> CompilerResult<ValueType>
     {
-        // Types must start with an identifier, struct, enum, or the name of the type
-        let token = ParseError::expect_named_identifier(self.stream.next(), "type")?;
+        // Types must start with struct, or the keyword naming the type
+        let token = ParseError::prevent_eof(self.stream.next())?;
 
-        let raw_type = match token.code_styled().as_str()
+        let raw_type = match token.token_type
         {
-            "struct" =>
+            TokenType::Keyword(Keyword::Struct) =>
             {
-                let struct_name = ParseError::expect_identifier(self.stream.next())?;
+                let struct_name = ParseError::expect_named_identifier(self.stream.next(), "struct name")?;
 
                 RawValueType::Struct(struct_name.code_styled())
             },
-            "void" => RawValueType::Void,
-            "char" => RawValueType::I8,
-            "short" => RawValueType::I16,
-            "int" => RawValueType::I32,
-            "long" => RawValueType::I64,
-            "unsigned" =>
+            TokenType::Keyword(Keyword::Void) => RawValueType::Void,
+            TokenType::Keyword(Keyword::Char) => RawValueType::I8,
+            TokenType::Keyword(Keyword::Short) => RawValueType::I16,
+            TokenType::Keyword(Keyword::Int) => RawValueType::I32,
+            TokenType::Keyword(Keyword::Long) => RawValueType::I64,
+            TokenType::Keyword(Keyword::Unsigned) =>
             {
                 self.parse_base_integer_type()?.make_unsigned()
             },
-            "signed" =>
+            TokenType::Keyword(Keyword::Signed) =>
             {
                 self.parse_base_integer_type()?.make_signed()
             },
@@ -267,22 +590,7 @@ impl<'a, S: std::iter::Iterator<Item = &'a Token>> Parser<'a, S>
                 return Err(ParseError::syntax_error(format!("Expected type, got {}", token.code_styled()), &token).into())
             }
         };
-        
-        let mut reference_count = 0;
-
-        while let Some(token) = self.stream.peek()
-        {
-            if token.token_type == TokenType::Symbol(format!("*"))
-            {
-                self.stream.next();
-                reference_count += 1;
-            }
-            else
-            {
-                break;
-            }
-        }
 
-        Ok(ValueType{references: reference_count, value_type: raw_type})
+        Ok(ValueType{references: self.parse_pointers(), value_type: raw_type})
     }
 }
\ No newline at end of file
@@ -11,7 +11,10 @@ pub enum ParseTreeNode
     CompilationUnit{children: Vec<ParseTreeNode>},
     Function{name: String, return_type: ValueType, arguments: Vec<(String, ValueType, Token)>, child: Box<ParseTreeNode>, name_token: Token },
     StatementBlock{children: Vec<ParseTreeNode>},
+    Declaration{children: Vec<ParseTreeNode>},
+    Declarator{name: String, value_type: ValueType, child: Option<Box<ParseTreeNode>>, name_token: Token},
     ReturnStatement{child: Option<Box<ParseTreeNode>>},
+    ExpressionStatement{child: Option<Box<ParseTreeNode>>},
     ConstantExpression{value: Value, token: Token},
     VariableExpression{name: String, token: Token},
     PostfixExpression{operation: PostfixExpressionOperation, children: Vec<ParseTreeNode>, optoken: Token},
@@ -32,6 +35,17 @@ pub enum ParseTreeNode
     CommaExpression{children: Vec<ParseTreeNode>, optoken: Token},
     IfStatement{children: Vec<ParseTreeNode>},
     WhileLoop{children: Vec<ParseTreeNode>},
+    DoWhileLoop{children: Vec<ParseTreeNode>},
+    ForLoop{init: Box<ParseTreeNode>, condition: Option<Box<ParseTreeNode>>, step: Option<Box<ParseTreeNode>>, child: Box<ParseTreeNode>},
+    BreakStatement{token: Token},
+    ContinueStatement{token: Token},
+    SwitchStatement{children: Vec<ParseTreeNode>, token: Token},
+    CaseStatement{children: Vec<ParseTreeNode>, token: Token},
+    DefaultStatement{child: Box<ParseTreeNode>, token: Token},
+    LabeledStatement{name: String, child: Box<ParseTreeNode>, token: Token},
+    GotoStatement{name: String, token: Token},
+    ComputedGotoStatement{child: Box<ParseTreeNode>, token: Token},
+    LabelAddressExpression{name: String, token: Token},
 }
 
 impl ParseTreeNode
@@ -81,7 +95,10 @@ impl ParseTreeNode
             ParseTreeNode::CompilationUnit { children } => Some(children.to_vec()),
             ParseTreeNode::Function { child, .. } => Some(vec![(**child).clone()]),
             ParseTreeNode::StatementBlock { children } => Some(children.to_vec()),
+            ParseTreeNode::Declaration { children } => Some(children.to_vec()),
+            ParseTreeNode::Declarator { child, .. } => child.as_ref().map(|c| vec![(**c).clone()]),
             ParseTreeNode::ReturnStatement { child } => child.as_ref().map(|c| vec![(**c).clone()]),
+            ParseTreeNode::ExpressionStatement { child } => child.as_ref().map(|c| vec![(**c).clone()]),
             ParseTreeNode::ConstantExpression{ .. } => None,
             ParseTreeNode::VariableExpression { .. } => None,
             ParseTreeNode::PostfixExpression { children, .. } => Some(children.to_vec()),
@@ -102,6 +119,20 @@ impl ParseTreeNode
             ParseTreeNode::CommaExpression { children, .. } => Some(children.to_vec()),
             ParseTreeNode::IfStatement { children } => Some(children.to_vec()),
             ParseTreeNode::WhileLoop { children } => Some(children.to_vec()),
+            ParseTreeNode::DoWhileLoop { children } => Some(children.to_vec()),
+            ParseTreeNode::ForLoop { init, condition, step, child } =>
+            {
+                Some(std::iter::once(init).chain(condition).chain(step).chain(std::iter::once(child)).map(|c| (**c).clone()).collect())
+            },
+            ParseTreeNode::BreakStatement { .. } => None,
+            ParseTreeNode::ContinueStatement { .. } => None,
+            ParseTreeNode::SwitchStatement { children, .. } => Some(children.to_vec()),
+            ParseTreeNode::CaseStatement { children, .. } => Some(children.to_vec()),
+            ParseTreeNode::DefaultStatement { child, .. } => Some(vec![(**child).clone()]),
+            ParseTreeNode::LabeledStatement { child, .. } => Some(vec![(**child).clone()]),
+            ParseTreeNode::GotoStatement { .. } => None,
+            ParseTreeNode::ComputedGotoStatement { child, .. } => Some(vec![(**child).clone()]),
+            ParseTreeNode::LabelAddressExpression { .. } => None,
         }
     }
 }
@@ -115,7 +146,10 @@ impl std::fmt::Display for ParseTreeNode
             ParseTreeNode::CompilationUnit { .. } => write!(f, "CompilationUnit"),
             ParseTreeNode::Function { name, return_type, arguments, .. } => write!(f, "Function {}({}) -> {}", name, render_arguments(arguments), return_type),
             ParseTreeNode::StatementBlock { .. } => write!(f, "StatementBlock"),
+            ParseTreeNode::Declaration { .. } => write!(f, "Declaration"),
+            ParseTreeNode::Declarator { name, value_type, .. } => write!(f, "Declarator {} {}", name, value_type),
             ParseTreeNode::ReturnStatement { .. } => write!(f, "ReturnStatement"),
+            ParseTreeNode::ExpressionStatement { .. } => write!(f, "ExpressionStatement"),
             ParseTreeNode::ConstantExpression{ value, .. } => write!(f, "Value {}", value),
             ParseTreeNode::VariableExpression{ name, .. } => write!(f, "Variable {}", name),
             ParseTreeNode::PostfixExpression { operation, .. } => write!(f, "PoastfixExpression {:?}", operation),
@@ -136,6 +170,17 @@ impl std::fmt::Display for ParseTreeNode
             ParseTreeNode::CommaExpression { .. } => write!(f, "CommaExpression"),
             ParseTreeNode::IfStatement { .. } => write!(f, "IfStatement"),
             ParseTreeNode::WhileLoop { .. } => write!(f, "WhileLoop"),
+            ParseTreeNode::DoWhileLoop { .. } => write!(f, "DoWhileLoop"),
+            ParseTreeNode::ForLoop { .. } => write!(f, "ForLoop"),
+            ParseTreeNode::BreakStatement { .. } => write!(f, "BreakStatement"),
+            ParseTreeNode::ContinueStatement { .. } => write!(f, "ContinueStatement"),
+            ParseTreeNode::SwitchStatement { .. } => write!(f, "SwitchStatement"),
+            ParseTreeNode::CaseStatement { .. } => write!(f, "CaseStatement"),
+            ParseTreeNode::DefaultStatement { .. } => write!(f, "DefaultStatement"),
+            ParseTreeNode::LabeledStatement { name, .. } => write!(f, "LabeledStatement {}", name),
+            ParseTreeNode::GotoStatement { name, .. } => write!(f, "GotoStatement {}", name),
+            ParseTreeNode::ComputedGotoStatement { .. } => write!(f, "ComputedGotoStatement"),
+            ParseTreeNode::LabelAddressExpression { name, .. } => write!(f, "LabelAddress {}", name),
         }
     }
 }
@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+use crate::tokenizer::EncodingPrefix;
+
 /// Value Enumeration
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Value
@@ -18,13 +20,43 @@ impl Value
             value_type: RawValueType::I32.into()
         }
     }
+
+    pub fn integer_constant(value: u64, value_type: RawValueType) -> Self
+    {
+        Self
+        {
+            value,
+            value_type: value_type.into()
+        }
+    }
+
+    /// Floating constants store the bit pattern of the value in the width of their type
+    pub fn floating_constant(value: f64, value_type: RawValueType) -> Self
+    {
+        let bits = match value_type
+        {
+            RawValueType::F32 => (value as f32).to_bits() as u64,
+            _ => value.to_bits()
+        };
+
+        Self
+        {
+            value: bits,
+            value_type: value_type.into()
+        }
+    }
 }
 
 impl std::fmt::Display for Value
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
     {
-        write!(f, "{} {}", self.value, self.value_type)
+        match self.value_type.value_type
+        {
+            RawValueType::F32 => write!(f, "{} {}", f32::from_bits(self.value as u32), self.value_type),
+            RawValueType::F64 | RawValueType::F128 => write!(f, "{} {}", f64::from_bits(self.value), self.value_type),
+            _ => write!(f, "{} {}", self.value, self.value_type)
+        }
     }
 }
 
@@ -36,6 +68,15 @@ pub struct ValueType
     pub value_type: RawValueType
 }
 
+impl ValueType
+{
+    /// Type of the value a pointer of this type points to, or None if this isn't a pointer
+    pub fn dereferenced(&self) -> Option<ValueType>
+    {
+        (self.references > 0).then(|| ValueType { references: self.references - 1, value_type: self.value_type.clone() })
+    }
+}
+
 impl std::fmt::Display for ValueType
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
@@ -58,6 +99,7 @@ pub enum RawValueType
     Void,
     I8, I16, I32, I64,
     U8, U16, U32, U64,
+    F32, F64, F128,
     Struct(String)
 }
 
@@ -104,6 +146,58 @@ impl RawValueType
     {
         matches!(self, RawValueType::U8 | RawValueType::U16 | RawValueType::U32 | RawValueType::U64)
     }
+
+    pub fn is_floating(&self) -> bool
+    {
+        matches!(self, RawValueType::F32 | RawValueType::F64 | RawValueType::F128)
+    }
+
+    /// Type of the elements of a string literal with the given prefix, which is also the type of a prefixed character constant
+    pub fn character_type(prefix: EncodingPrefix) -> Self
+    {
+        match prefix
+        {
+            EncodingPrefix::None => RawValueType::I8,
+            EncodingPrefix::Utf8 => RawValueType::U8,
+            EncodingPrefix::Utf16 => RawValueType::U16,
+            EncodingPrefix::Utf32 => RawValueType::U32,
+            EncodingPrefix::Wide => RawValueType::I32
+        }
+    }
+
+    /// Size of the type in bytes
+    pub fn size(&self) -> usize
+    {
+        match self
+        {
+            RawValueType::Void => 0,
+            RawValueType::I8 | RawValueType::U8 => 1,
+            RawValueType::I16 | RawValueType::U16 => 2,
+            RawValueType::I32 | RawValueType::U32 | RawValueType::F32 => 4,
+            RawValueType::I64 | RawValueType::U64 | RawValueType::F64 => 8,
+            RawValueType::F128 => 16,
+            RawValueType::Struct(_) => 0,
+        }
+    }
+
+    /// Returns true if the integer type can hold the given value
+    pub fn can_represent(&self, value: u64) -> bool
+    {
+        let bits = self.size() * 8;
+
+        if self.is_signed()
+        {
+            value < (1u64 << (bits - 1))
+        }
+        else if self.is_unsigned()
+        {
+            bits == 64 || value < (1u64 << bits)
+        }
+        else
+        {
+            false
+        }
+    }
 }
 
 impl std::fmt::Display for RawValueType
@@ -121,6 +215,9 @@ impl std::fmt::Display for RawValueType
             RawValueType::U16 => write!(f, "u16"),
             RawValueType::U32 => write!(f, "u32"),
             RawValueType::U64 => write!(f, "u64"),
+            RawValueType::F32 => write!(f, "f32"),
+            RawValueType::F64 => write!(f, "f64"),
+            RawValueType::F128 => write!(f, "f128"),
             RawValueType::Struct(name) => write!(f, "struct {}", name),
         }
     }
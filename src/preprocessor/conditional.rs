@@ -0,0 +1,468 @@
+use crate::errors::*;
+use crate::preprocessor::PreprocessorError;
+use crate::settings::Standard;
+use crate::tokenizer::{Token, TokenType, TokenizerError, Punct, IntegerConstant, IntegerSuffix, EncodingPrefix};
+
+use super::*;
+
+/// State of one level of conditional inclusion
+#[derive(Debug, Clone)]
+pub struct Conditional
+{
+    /// The directive which opened the conditional
+    pub directive: Token,
+    /// True if the lines of the current group are kept
+    pub active: bool,
+    /// True once a group has been kept, or if the whole conditional is inside a skipped group
+    pub taken: bool,
+    /// True once the #else group has been reached
+    pub seen_else: bool
+}
+
+impl Conditional
+{
+    pub fn new(directive: Token, enclosing_active: bool, condition: bool) -> Self
+    {
+        Self
+        {
+            directive,
+            active: enclosing_active && condition,
+            taken: !enclosing_active || condition,
+            seen_else: false
+        }
+    }
+}
+
+/// Value of a preprocessor expression, which is computed with the semantics of intmax_t or uintmax_t
+#[derive(Debug, Clone, Copy)]
+struct ConditionValue
+{
+    value: u64,
+    unsigned: bool
+}
+
+impl ConditionValue
+{
+    fn signed(value: i64) -> Self
+    {
+        Self { value: value as u64, unsigned: false }
+    }
+
+    fn boolean(value: bool) -> Self
+    {
+        Self::signed(value as i64)
+    }
+
+    fn is_true(&self) -> bool
+    {
+        self.value != 0
+    }
+
+    fn is_negative(&self) -> bool
+    {
+        !self.unsigned && (self.value as i64) < 0
+    }
+}
+
+/// Binding strength of the binary operators, higher numbers bind tighter
+fn binary_precedence(punct: Punct) -> Option<usize>
+{
+    match punct
+    {
+        Punct::LogicalOr => Some(1),
+        Punct::LogicalAnd => Some(2),
+        Punct::Pipe => Some(3),
+        Punct::Caret => Some(4),
+        Punct::Ampersand => Some(5),
+        Punct::Equal | Punct::NotEqual => Some(6),
+        Punct::LessThan | Punct::GreaterThan | Punct::LessThanEqual | Punct::GreaterThanEqual => Some(7),
+        Punct::ShiftLeft | Punct::ShiftRight => Some(8),
+        Punct::Plus | Punct::Minus => Some(9),
+        Punct::Asterisk | Punct::Slash | Punct::Percent => Some(10),
+        _ => None
+    }
+}
+
+/// Evaluator for the controlling expression of an #if or #elif directive
+struct ConditionEvaluator<'t>
+{
+    tokens: &'t [Token],
+    position: usize,
+    directive: &'t Token,
+    standard: Standard
+}
+
+impl<'t> ConditionEvaluator<'t>
+{
+    fn peek(&self) -> Option<&'t Token>
+    {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_punct(&self, punct: Punct) -> bool
+    {
+        self.peek().is_some_and(|token| token.is_punct(punct))
+    }
+
+    fn error<T>(&self, message: String, token: Option<&Token>) -> CompilerResult<T>
+    {
+        Err(PreprocessorError::syntax_error(message, token.unwrap_or(self.directive)).into())
+    }
+
+    fn expect_punct(&mut self, punct: Punct) -> CompilerResult<()>
+    {
+        if self.peek_punct(punct)
+        {
+            self.position += 1;
+            Ok(())
+        }
+        else
+        {
+            let got = self.peek().map(|token| token.code_styled()).unwrap_or_else(|| String::from("end of line"));
+            self.error(format!("Expected {} in preprocessor expression, got {}", punct, got), self.peek())
+        }
+    }
+
+    /// Parse a comma expression, errors such as division by zero are only raised when evaluate is set
+    fn expression(&mut self, evaluate: bool) -> CompilerResult<ConditionValue>
+    {
+        let mut value = self.conditional(evaluate)?;
+
+        while self.peek_punct(Punct::Comma)
+        {
+            self.position += 1;
+            value = self.conditional(evaluate)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Parse a conditional expression
+    fn conditional(&mut self, evaluate: bool) -> CompilerResult<ConditionValue>
+    {
+        let condition = self.binary(1, evaluate)?;
+
+        if !self.peek_punct(Punct::Question)
+        {
+            return Ok(condition);
+        }
+
+        self.position += 1;
+
+        let first = self.expression(evaluate && condition.is_true())?;
+        self.expect_punct(Punct::Colon)?;
+        let second = self.conditional(evaluate && !condition.is_true())?;
+
+        // The result has the common type of both alternatives
+        let unsigned = first.unsigned || second.unsigned;
+        let chosen = if condition.is_true() { first } else { second };
+
+        Ok(ConditionValue { value: chosen.value, unsigned })
+    }
+
+    /// Parse binary operators binding at least as tightly as the given precedence
+    fn binary(&mut self, minimum: usize, evaluate: bool) -> CompilerResult<ConditionValue>
+    {
+        let mut left = self.unary(evaluate)?;
+
+        loop
+        {
+            let (operator, precedence) = match self.peek().map(|token| &token.token_type)
+            {
+                Some(TokenType::Punct(punct)) => match binary_precedence(*punct)
+                {
+                    Some(precedence) if precedence >= minimum => (*punct, precedence),
+                    _ => return Ok(left)
+                },
+                _ => return Ok(left)
+            };
+
+            let operator_token = self.peek().unwrap();
+            self.position += 1;
+
+            // The right side of a logical operator is only evaluated when it can change the result
+            let evaluate_right = match operator
+            {
+                Punct::LogicalAnd => evaluate && left.is_true(),
+                Punct::LogicalOr => evaluate && !left.is_true(),
+                _ => evaluate
+            };
+
+            let right = self.binary(precedence + 1, evaluate_right)?;
+
+            left = self.apply(operator, left, right, evaluate, operator_token)?;
+        }
+    }
+
+    /// Apply a binary operator using the usual arithmetic conversions
+    fn apply(&self, operator: Punct, left: ConditionValue, right: ConditionValue, evaluate: bool, token: &Token) -> CompilerResult<ConditionValue>
+    {
+        let unsigned = left.unsigned || right.unsigned;
+        let (a, b) = (left.value, right.value);
+        let (sa, sb) = (a as i64, b as i64);
+
+        let arithmetic = |value: u64| ConditionValue { value, unsigned };
+
+        Ok(match operator
+        {
+            Punct::LogicalOr => ConditionValue::boolean(left.is_true() || right.is_true()),
+            Punct::LogicalAnd => ConditionValue::boolean(left.is_true() && right.is_true()),
+            Punct::Pipe => arithmetic(a | b),
+            Punct::Caret => arithmetic(a ^ b),
+            Punct::Ampersand => arithmetic(a & b),
+            Punct::Equal => ConditionValue::boolean(a == b),
+            Punct::NotEqual => ConditionValue::boolean(a != b),
+            Punct::LessThan => ConditionValue::boolean(if unsigned { a < b } else { sa < sb }),
+            Punct::GreaterThan => ConditionValue::boolean(if unsigned { a > b } else { sa > sb }),
+            Punct::LessThanEqual => ConditionValue::boolean(if unsigned { a <= b } else { sa <= sb }),
+            Punct::GreaterThanEqual => ConditionValue::boolean(if unsigned { a >= b } else { sa >= sb }),
+            Punct::ShiftLeft | Punct::ShiftRight =>
+            {
+                // Shifts take the type of their left operand, and shifting by a negative amount shifts the other way
+                let left_shift = (operator == Punct::ShiftLeft) != right.is_negative();
+                let amount = if right.is_negative() { sb.unsigned_abs() } else { b };
+
+                let value = if left_shift
+                {
+                    if amount >= 64 { 0 } else { a << amount }
+                }
+                else if left.unsigned
+                {
+                    if amount >= 64 { 0 } else { a >> amount }
+                }
+                else
+                {
+                    (sa >> amount.min(63)) as u64
+                };
+
+                ConditionValue { value, unsigned: left.unsigned }
+            },
+            Punct::Plus => arithmetic(a.wrapping_add(b)),
+            Punct::Minus => arithmetic(a.wrapping_sub(b)),
+            Punct::Asterisk => arithmetic(a.wrapping_mul(b)),
+            Punct::Slash | Punct::Percent =>
+            {
+                if b == 0
+                {
+                    if evaluate
+                    {
+                        return self.error(String::from("Division by zero in preprocessor expression"), Some(token));
+                    }
+
+                    return Ok(arithmetic(0));
+                }
+
+                match (operator, unsigned)
+                {
+                    (Punct::Slash, true) => arithmetic(a / b),
+                    (Punct::Slash, false) => arithmetic(sa.wrapping_div(sb) as u64),
+                    (_, true) => arithmetic(a % b),
+                    (_, false) => arithmetic(sa.wrapping_rem(sb) as u64)
+                }
+            },
+            _ => unreachable!()
+        })
+    }
+
+    /// Parse a unary expression
+    fn unary(&mut self, evaluate: bool) -> CompilerResult<ConditionValue>
+    {
+        let token = match self.peek()
+        {
+            Some(token) => token,
+            None => return self.error(String::from("Expected value in preprocessor expression, got end of line"), None)
+        };
+
+        let operator = match &token.token_type
+        {
+            TokenType::Punct(punct @ (Punct::Plus | Punct::Minus | Punct::Tilde | Punct::Exclamation)) => *punct,
+            _ => return self.primary(evaluate)
+        };
+
+        self.position += 1;
+
+        let value = self.unary(evaluate)?;
+
+        Ok(match operator
+        {
+            Punct::Plus => value,
+            Punct::Minus => ConditionValue { value: value.value.wrapping_neg(), unsigned: value.unsigned },
+            Punct::Tilde => ConditionValue { value: !value.value, unsigned: value.unsigned },
+            _ => ConditionValue::boolean(!value.is_true())
+        })
+    }
+
+    /// Parse a primary expression
+    fn primary(&mut self, evaluate: bool) -> CompilerResult<ConditionValue>
+    {
+        let token = self.peek().unwrap();
+
+        self.position += 1;
+
+        match &token.token_type
+        {
+            TokenType::Punct(Punct::LeftParen) =>
+            {
+                let value = self.expression(evaluate)?;
+                self.expect_punct(Punct::RightParen)?;
+
+                Ok(value)
+            },
+            TokenType::IntegerLiteral(constant) =>
+            {
+                let unsigned = matches!(constant.suffix, IntegerSuffix::Unsigned | IntegerSuffix::UnsignedLong | IntegerSuffix::UnsignedLongLong);
+
+                // Constants which don't fit in intmax_t are treated as unsigned
+                Ok(ConditionValue { value: constant.value, unsigned: unsigned || constant.value > i64::MAX as u64 })
+            },
+            TokenType::CharacterLiteral(literal) =>
+            {
+                match literal.prefix
+                {
+                    EncodingPrefix::None => Ok(ConditionValue::signed(literal.units.iter().fold(0u32, |value, unit| (value << 8) | unit) as i32 as i64)),
+                    EncodingPrefix::Wide => Ok(ConditionValue::signed(literal.units.first().copied().unwrap_or(0) as i32 as i64)),
+                    EncodingPrefix::Utf32 => Ok(ConditionValue { value: literal.units.first().copied().unwrap_or(0) as u64, unsigned: true }),
+                    _ => Ok(ConditionValue::signed(literal.units.first().copied().unwrap_or(0) as i64))
+                }
+            },
+            // The boolean constants are keywords in C23, and keep their values in conditions
+            TokenType::Identifier(name) if name == "true" && self.standard == Standard::C23 => Ok(ConditionValue::signed(1)),
+            // Any other identifier left after macro replacement is replaced with zero
+            TokenType::Identifier(_) => Ok(ConditionValue::signed(0)),
+            TokenType::Other(_, error) => Err(TokenizerError::new(error.clone(), token.location.clone()).into()),
+            _ => self.error(format!("Unexpected {} in preprocessor expression", token.code_styled()), Some(token))
+        }
+    }
+}
+
+/// Construct an integer constant token to stand in for the result of a preprocessor operator
+fn integer_token(value: bool, location_token: &Token) -> Token
+{
+    let value = value as u64;
+    let constant = IntegerConstant { value, decimal: true, suffix: IntegerSuffix::None, spelling: value.to_string() };
+
+    let mut token = Token::construct(TokenType::IntegerLiteral(constant), location_token.location.clone());
+    token.original_location = location_token.original_location.clone();
+
+    token
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Returns true if the lines at the current position are kept
+    pub fn is_active(&self) -> bool
+    {
+        self.if_stack.iter().all(|conditional| conditional.active)
+    }
+
+    /// Determine the condition given by an #if, #ifdef or #ifndef directive, or one of their #elif forms
+    pub fn directive_condition(&self, directive: &Token, line: Vec<Token>) -> CompilerResult<bool>
+    {
+        match directive.code_styled().as_str()
+        {
+            "#if" | "#elif" => self.evaluate_condition(directive, line),
+            name =>
+            {
+                let identifier = match line.first()
+                {
+                    Some(token) => PreprocessorError::expect_identifier(Some(token))?.code_styled(),
+                    None => return Err(PreprocessorError::syntax_error(format!("Expected identifier after {}", name), directive).into())
+                };
+
+                Ok(self.is_defined(&identifier) != name.ends_with("ndef"))
+            }
+        }
+    }
+
+    /// Evaluate the controlling expression of an #if or #elif directive
+    pub fn evaluate_condition(&self, directive: &Token, line: Vec<Token>) -> CompilerResult<bool>
+    {
+        if line.is_empty()
+        {
+            return Err(PreprocessorError::syntax_error(format!("{} with no expression", directive.code_styled()), directive).into());
+        }
+
+        // Operators which look at names are handled before any macros are replaced
+        let line = self.replace_condition_operators(line)?;
+        let tokens = self.expand_tokens(line)?;
+
+        let mut evaluator = ConditionEvaluator { tokens: &tokens, position: 0, directive, standard: self.compiler.settings().standard };
+        let value = evaluator.expression(true)?;
+
+        if let Some(token) = evaluator.peek()
+        {
+            return Err(PreprocessorError::syntax_error(format!("Unexpected {} in preprocessor expression", token.code_styled()), token).into());
+        }
+
+        Ok(value.is_true())
+    }
+
+    /// Replace uses of defined and __has_include with the integer constants they evaluate to
+    fn replace_condition_operators(&self, line: Vec<Token>) -> CompilerResult<Vec<Token>>
+    {
+        let mut result = Vec::new();
+        let mut tokens = line.into_iter().peekable();
+
+        while let Some(token) = tokens.next()
+        {
+            match &token.token_type
+            {
+                TokenType::Identifier(name) if name == "defined" =>
+                {
+                    // The operand may optionally be wrapped in parentheses
+                    let parenthesized = tokens.peek().is_some_and(|next| next.is_punct(Punct::LeftParen));
+
+                    if parenthesized
+                    {
+                        tokens.next();
+                    }
+
+                    let identifier = match tokens.next()
+                    {
+                        Some(next) => PreprocessorError::expect_identifier(Some(&next))?,
+                        None => return Err(PreprocessorError::syntax_error(String::from("Expected identifier after defined"), &token).into())
+                    };
+
+                    if parenthesized
+                    {
+                        match tokens.next()
+                        {
+                            Some(next) => PreprocessorError::expect_punct(Some(&next), Punct::RightParen)?,
+                            None => return Err(PreprocessorError::syntax_error(String::from("Expected ) after the operand of defined"), &identifier).into())
+                        }
+                    }
+
+                    result.push(integer_token(self.is_defined(&identifier.code_styled()), &token));
+                },
+                TokenType::Identifier(name) if name == "__has_include" || name == "__has_include_next" =>
+                {
+                    match tokens.next()
+                    {
+                        Some(next) => PreprocessorError::expect_punct(Some(&next), Punct::LeftParen)?,
+                        None => return Err(PreprocessorError::syntax_error(format!("Expected ( after {}", name), &token).into())
+                    }
+
+                    let mut operand = Vec::new();
+
+                    for next in tokens.by_ref()
+                    {
+                        if next.is_punct(Punct::RightParen)
+                        {
+                            break;
+                        }
+
+                        operand.push(next);
+                    }
+
+                    let (filename, angled) = self.header_name(&token, operand)?;
+                    let found = self.search_include_paths(&filename, angled, name.ends_with("next")).is_some();
+
+                    result.push(integer_token(found, &token));
+                },
+                _ => result.push(token)
+            }
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+/// Longest line of a make rule before it is continued on the next line
+const MAX_RULE_LINE: usize = 76;
+
+/// Escape a filename for use in a makefile
+fn escape_make(filename: &str) -> String
+{
+    let mut escaped = String::new();
+
+    for c in filename.chars()
+    {
+        match c
+        {
+            ' ' | '#' => escaped.push('\\'),
+            '$' => escaped.push('$'),
+            _ => {}
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Headers which were included, once each in the order they were first included
+    fn included_files(&self, system: bool) -> Vec<&str>
+    {
+        let mut files: Vec<&str> = Vec::new();
+
+        for edge in self.include_edges.iter().filter(|edge| system || !edge.system)
+        {
+            if !files.contains(&edge.included.as_str())
+            {
+                files.push(&edge.included);
+            }
+        }
+
+        files
+    }
+
+    /// Write a make rule making the targets depend on the source file and every header it included
+    ///
+    /// Headers from system include directories are only listed if asked for, and each header may be given a rule of
+    /// its own with no dependencies so that removing it doesn't break the build.
+    pub fn make_rule(&self, source: &str, targets: &[String], system: bool, phony: bool) -> String
+    {
+        let headers = self.included_files(system);
+
+        let targets = targets.iter().map(|target| escape_make(target)).collect::<Vec<_>>();
+        let mut rule = format!("{}:", targets.join(" "));
+        let mut line_length = rule.len();
+
+        // Long rules are continued on the next line
+        for dependency in std::iter::once(source).chain(headers.iter().copied()).map(escape_make)
+        {
+            if line_length + dependency.len() + 1 > MAX_RULE_LINE && line_length > 0
+            {
+                rule += " \\\n";
+                line_length = 0;
+            }
+
+            rule += &format!(" {}", dependency);
+            line_length += dependency.len() + 1;
+        }
+
+        rule.push('\n');
+
+        if phony
+        {
+            for header in headers
+            {
+                rule += &format!("\n{}:\n", escape_make(header));
+            }
+        }
+
+        rule
+    }
+
+    /// Write the graph of which files included which in the Graphviz dot language, with system headers dashed
+    pub fn include_graph_dot(&self, source: &str) -> String
+    {
+        let mut graph = String::from("digraph includes\n{\n");
+
+        graph += &format!("    \"{}\";\n", source.escape_default());
+
+        for header in self.included_files(true)
+        {
+            let system = self.include_edges.iter().any(|edge| edge.included == header && edge.system);
+            let style = if system { " [style=dashed]" } else { "" };
+
+            graph += &format!("    \"{}\"{};\n", header.escape_default(), style);
+        }
+
+        let mut edges: Vec<(&str, &str)> = Vec::new();
+
+        for edge in &self.include_edges
+        {
+            if !edges.contains(&(edge.includer.as_str(), edge.included.as_str()))
+            {
+                edges.push((&edge.includer, &edge.included));
+            }
+        }
+
+        for (includer, included) in edges
+        {
+            graph += &format!("    \"{}\" -> \"{}\";\n", includer.escape_default(), included.escape_default());
+        }
+
+        graph.push_str("}\n");
+
+        graph
+    }
+}
@@ -5,13 +5,16 @@ pub struct PreprocessorError
 {
     pub error: PreprocessorErrorType,
     pub location: Location,
-    pub original_location: Option<Location>,
-    pub arrow_length: usize
+    pub original_location: Option<Box<Location>>,
+    /// Macro expansions the error happened in, innermost first
+    pub expansions: Vec<ExpansionFrame>
 }
 
 pub enum PreprocessorErrorType
 {
-    SyntaxError(String)
+    SyntaxError(String),
+    /// The message of an #error directive
+    ErrorDirective(String)
 }
 
 impl std::fmt::Display for PreprocessorError
@@ -21,6 +24,7 @@ impl std::fmt::Display for PreprocessorError
         match &self.error
         {
             PreprocessorErrorType::SyntaxError(text) => write!(f, "Syntax Error {}", text)?,
+            PreprocessorErrorType::ErrorDirective(text) => write!(f, "#error {}", text)?,
         }
 
         write!(f, " at {}", self.location)?;
@@ -42,8 +46,19 @@ impl PreprocessorError
         {
             location: token.location.clone(),
             error: PreprocessorErrorType::SyntaxError(error),
-            original_location: token.original_location.clone(),
-            arrow_length: token.code_styled().len(),
+            original_location: token.original_location.clone().map(Box::new),
+            expansions: token.expansions.clone(),
+        }
+    }
+
+    pub fn error_directive(message: String, token: &Token) -> Self
+    {
+        Self
+        {
+            location: token.location.clone(),
+            error: PreprocessorErrorType::ErrorDirective(message),
+            original_location: token.original_location.clone().map(Box::new),
+            expansions: token.expansions.clone(),
         }
     }
 
@@ -78,32 +93,17 @@ impl PreprocessorError
         }
     }
 
-    pub fn expect_string_literal(token: Option<&Token>) -> Result<Token, PreprocessorError>
+    pub fn expect_punct(token: Option<&Token>, punct: Punct) -> Result<(), PreprocessorError>
     {
         let token = PreprocessorError::prevent_eof(token)?;
 
-        if let TokenType::StringLiteral(_) = &token.token_type
+        if token.is_punct(punct)
         {
-            Ok(token)
+            Ok(())
         }
         else
         {
-            Err(PreprocessorError::syntax_error(format!("Expected string literal, got {}", token.code_styled()), &token))
-        }
-    }
-
-    pub fn expect_symbol(token: Option<&Token>, symbol: &str) -> Result<(), PreprocessorError>
-    {
-        let token = PreprocessorError::prevent_eof(token)?;
-
-        if let TokenType::Symbol(token_symbol) = &token.token_type
-        {
-            if token_symbol == symbol
-            {
-                return Ok(());
-            }
+            Err(PreprocessorError::syntax_error(format!("Expected {}, got {}", punct, token.code_styled()), &token))
         }
-
-        Err(PreprocessorError::syntax_error(format!("Expected {}, got {}", symbol, token.code_styled()), &token))
     }
 }
\ No newline at end of file
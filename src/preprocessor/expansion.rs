@@ -0,0 +1,761 @@
+use std::collections::{HashSet, VecDeque};
+use std::iter::Peekable;
+
+use crate::errors::*;
+use crate::preprocessor::PreprocessorError;
+use crate::tokenizer::{Token, TokenType, Punct, TextLiteral, EncodingPrefix, ExpansionFrame, FileManager, tokenize, escape_literal};
+
+use super::*;
+
+/// Tokens produced by macro expansion which are waiting to be rescanned, in front of the rest of the file
+struct ExpansionInput<'f, 'x, I: Iterator<Item = &'x Token>>
+{
+    pending: VecDeque<Token>,
+    file: &'f mut Peekable<I>
+}
+
+impl<'f, 'x, I: Iterator<Item = &'x Token>> ExpansionInput<'f, 'x, I>
+{
+    /// Take the next token, continuing into the file once the pending tokens run out
+    fn next_token(&mut self, invocation: &Token) -> CompilerResult<Token>
+    {
+        if let Some(next) = self.pending.pop_front()
+        {
+            return Ok(next);
+        }
+
+        // The tokens of a directive line like #if or #include end without an end of file token
+        match self.file.peek()
+        {
+            Some(token) if token.is_preprocessor_directive() =>
+            {
+                Err(PreprocessorError::syntax_error(format!("Directive {} cannot appear in the arguments of macro {}", token.code_styled(), invocation.code_styled()), token).into())
+            },
+            Some(token) if !token.is_eof() => Ok(self.file.next().unwrap().clone()),
+            _ => Err(PreprocessorError::syntax_error(format!("Unterminated argument list invoking macro {}", invocation.code_styled()), invocation).into())
+        }
+    }
+
+    /// Look at the next token without taking it
+    fn peek(&mut self) -> Option<&Token>
+    {
+        match self.pending.front()
+        {
+            Some(token) => Some(token),
+            None => self.file.peek().copied()
+        }
+    }
+
+    /// Queue the replacement of a macro to be rescanned before anything else
+    fn push_expansion(&mut self, replacement: Vec<Token>)
+    {
+        for token in replacement.into_iter().rev()
+        {
+            self.pending.push_front(token);
+        }
+    }
+
+    /// Collect the arguments of a function like macro invocation, starting from its left paren, along with the right
+    /// paren which closes them
+    ///
+    /// Once the given number of arguments has been reached, commas stop separating arguments so that the variable
+    /// arguments of a variadic macro are collected together.
+    fn collect_arguments(&mut self, invocation: &Token, variadic_from: Option<usize>) -> CompilerResult<(Vec<Vec<Token>>, Token)>
+    {
+        // Step past the left paren
+        self.next_token(invocation)?;
+
+        let mut arguments = vec![Vec::new()];
+        let mut depth = 0;
+
+        loop
+        {
+            let token = self.next_token(invocation)?;
+
+            // Commas only separate arguments when they aren't nested inside parentheses
+            match &token.token_type
+            {
+                TokenType::Punct(Punct::RightParen) if depth == 0 => return Ok((arguments, token)),
+                TokenType::Punct(Punct::Comma) if depth == 0 && variadic_from != Some(arguments.len()) =>
+                {
+                    arguments.push(Vec::new());
+                    continue;
+                },
+                TokenType::Punct(Punct::LeftParen) => depth += 1,
+                TokenType::Punct(Punct::RightParen) => depth -= 1,
+                _ => {}
+            }
+
+            arguments.last_mut().unwrap().push(token);
+        }
+    }
+}
+
+/// The parameter name which refers to the variable arguments of a variadic macro
+pub const VA_ARGS: &str = "__VA_ARGS__";
+
+/// The operator whose group only appears in the replacement when there are variable arguments
+pub const VA_OPT: &str = "__VA_OPT__";
+
+/// Check if a token is the identifier with the given name
+fn is_name(token: &Token, name: &str) -> bool
+{
+    matches!(&token.token_type, TokenType::Identifier(identifier) if identifier == name)
+}
+
+/// Check if the parameters of a function like macro end with the variable arguments
+pub fn is_variadic(parameters: &[Token]) -> bool
+{
+    parameters.last().is_some_and(|parameter| is_name(parameter, VA_ARGS))
+}
+
+/// Find the right paren which closes the group of the `__VA_OPT__` at the given index of a macro body
+fn va_opt_group_end(body: &[Token], start: usize) -> Option<usize>
+{
+    if !body.get(start + 1)?.is_punct(Punct::LeftParen)
+    {
+        return None;
+    }
+
+    let mut depth = 0;
+
+    for (i, token) in body.iter().enumerate().skip(start + 1)
+    {
+        if token.is_punct(Punct::LeftParen)
+        {
+            depth += 1;
+        }
+        else if token.is_punct(Punct::RightParen)
+        {
+            depth -= 1;
+
+            if depth == 0
+            {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
+
+/// An element of a macro replacement before token pasting has been performed
+enum Piece
+{
+    Token(Token),
+    /// A `##` operator from the macro body
+    Paste(Token),
+    /// Stands in for an empty argument next to a `##` operator
+    Placemarker
+}
+
+/// The arguments of a macro invocation which are being substituted into its body
+struct Arguments<'s>
+{
+    parameters: &'s [Token],
+    raw: &'s [Vec<Token>],
+    /// Completely replaced arguments, each computed the first time it is needed
+    expanded: Vec<Option<Vec<Token>>>
+}
+
+impl<'s> Arguments<'s>
+{
+    fn new(parameters: &'s [Token], raw: &'s [Vec<Token>]) -> Self
+    {
+        Self { parameters, raw, expanded: vec![None; raw.len()] }
+    }
+
+    /// Find which parameter a token in a macro body refers to, if any
+    fn index(&self, token: &Token) -> Option<usize>
+    {
+        PreprocessorContext::parameter_index(self.parameters, token)
+    }
+
+    /// Check if the macro is variadic and was given no variable arguments
+    fn variadic_empty(&self) -> bool
+    {
+        is_variadic(self.parameters) && self.raw.last().is_none_or(|argument| argument.is_empty())
+    }
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Check the constraints on the `#`, `##` and `__VA_OPT__` operators in a macro replacement list
+    pub fn check_replacement_list(name: &str, parameters: Option<&[Token]>, body: &[Token]) -> CompilerResult<()>
+    {
+        let variadic = parameters.is_some_and(is_variadic);
+
+        for (i, token) in body.iter().enumerate()
+        {
+            if token.is_punct(Punct::HashHash) && (i == 0 || i == body.len() - 1)
+            {
+                return Err(PreprocessorError::syntax_error(format!("{} cannot appear at either end of the replacement list of macro {}", token.code_styled(), name), token).into());
+            }
+
+            if !variadic && (is_name(token, VA_ARGS) || is_name(token, VA_OPT))
+            {
+                return Err(PreprocessorError::syntax_error(format!("{} can only appear in the replacement list of a variadic macro", token.code_styled()), token).into());
+            }
+
+            if is_name(token, VA_OPT)
+            {
+                let end = va_opt_group_end(body, i)
+                    .ok_or_else(|| PreprocessorError::syntax_error(format!("{} must be followed by a parenthesized group", token.code_styled()), token))?;
+
+                Self::check_replacement_list(name, parameters, &body[i + 2..end])?;
+            }
+
+            // Only function like macros treat `#` as an operator
+            if let Some(parameters) = parameters
+            {
+                if token.is_punct(Punct::Hash) && body.get(i + 1).is_none_or(|next| Self::parameter_index(parameters, next).is_none() && !is_name(next, VA_OPT))
+                {
+                    return Err(PreprocessorError::syntax_error(format!("{} is not followed by a parameter of macro {}", token.code_styled(), name), token).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find which parameter a token in a macro body refers to, if any
+    fn parameter_index(parameters: &[Token], token: &Token) -> Option<usize>
+    {
+        if token.is_identifier()
+        {
+            parameters.iter().position(|parameter| parameter.token_type == token.token_type)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Get an argument after it has been completely macro replaced
+    fn expanded_argument(&self, arguments: &mut Arguments, index: usize) -> CompilerResult<Vec<Token>>
+    {
+        if arguments.expanded[index].is_none()
+        {
+            arguments.expanded[index] = Some(self.expand_tokens(arguments.raw[index].clone())?);
+        }
+
+        Ok(arguments.expanded[index].clone().unwrap())
+    }
+
+    /// Spell a sequence of tokens as a string literal for the `#` operator
+    ///
+    /// Every token is spelled as it was written. Only the quotes and backslashes of string and character literals are
+    /// escaped, so any other backslash starts an escape sequence of the new literal.
+    fn stringify(&self, argument: &[Token], operator: &Token) -> Token
+    {
+        let mut spelling = String::new();
+
+        for (i, token) in argument.iter().enumerate()
+        {
+            if i > 0 && token.leading_space
+            {
+                spelling.push(' ');
+            }
+
+            match &token.token_type
+            {
+                TokenType::StringLiteral(_) | TokenType::CharacterLiteral(_) => spelling += &escape_literal(&token.source_spelling(), '"'),
+                _ => spelling += &token.source_spelling()
+            }
+        }
+
+        let literal = tokenize(&FileManager::from_text("<stringize>", format!("\"{}\"", spelling)), self.compiler.settings());
+
+        // A spelling which isn't a valid literal, such as one ending in a backslash, is kept as it is
+        let literal = match literal.as_deref()
+        {
+            Ok([Token { token_type: TokenType::StringLiteral(literal), .. }, eof]) if eof.is_eof() => literal.clone(),
+            _ =>
+            {
+                let mut units = Vec::new();
+
+                for c in spelling.chars()
+                {
+                    EncodingPrefix::None.encode(c, &mut units);
+                }
+
+                TextLiteral { prefix: EncodingPrefix::None, units, source: None }
+            }
+        };
+
+        let mut token = operator.clone();
+        token.token_type = TokenType::StringLiteral(literal);
+        token
+    }
+
+    /// Join two tokens with the `##` operator, which must give a single valid token
+    fn paste(&self, left: &Token, right: &Token, operator: &Token) -> CompilerResult<Token>
+    {
+        let spelling = format!("{}{}", left.source_spelling(), right.source_spelling());
+        let invalid = || PreprocessorError::syntax_error(format!("Pasting {} and {} does not give a valid token", left.code_styled(), right.code_styled()), operator);
+
+        // The joined spelling is lexed again on its own, a directive means the result was a `#` followed by something
+        let tokens = tokenize(&FileManager::from_text("<paste>", spelling), self.compiler.settings()).map_err(|_| invalid())?;
+
+        match tokens.as_slice()
+        {
+            [pasted, eof] if eof.is_eof() && !pasted.is_preprocessor_directive() && !pasted.is_other() =>
+            {
+                let mut token = left.clone();
+                token.token_type = pasted.token_type.clone();
+                token.hide_set = left.hide_set.intersection(&right.hide_set).cloned().collect();
+                Ok(token)
+            },
+            _ => Err(invalid().into())
+        }
+    }
+
+    /// Build the replacement for a macro invocation, substituting arguments and performing the `#` and `##` operators
+    ///
+    /// Every token of the replacement has the given hide set added to its own, so that it can't be replaced by any of
+    /// the macros it came from.
+    fn substitute(&self, invocation: &Token, body: &[Token], arguments: &mut Arguments, hide_set: &HashSet<String>) -> CompilerResult<Vec<Token>>
+    {
+        let body = self.instantiate(body, invocation);
+        let pieces = self.substitution_pieces(&body, arguments)?;
+
+        let mut replacement = self.paste_pieces(pieces)?;
+        let frame = self.expansion_frame(invocation);
+
+        for token in &mut replacement
+        {
+            token.hide_set.extend(hide_set.iter().cloned());
+
+            // Tokens from the body already know they are in this expansion, but arguments don't
+            if token.expansions.first() != Some(&frame)
+            {
+                token.expansions.insert(0, frame.clone());
+            }
+        }
+
+        // The replacement takes the place of the invocation, including any whitespace before it
+        if let Some(first) = replacement.first_mut()
+        {
+            first.leading_space = invocation.leading_space;
+        }
+
+        Ok(replacement)
+    }
+
+    /// Substitute arguments into part of a macro body, performing the `#` operator and leaving `##` operators to be pasted
+    fn substitution_pieces(&self, body: &[Token], arguments: &mut Arguments) -> CompilerResult<Vec<Piece>>
+    {
+        let mut pieces = Vec::new();
+
+        let is_paste = |i: Option<usize>| i.and_then(|i| body.get(i)).is_some_and(|token| token.is_punct(Punct::HashHash));
+
+        let mut i = 0;
+
+        while i < body.len()
+        {
+            let token = &body[i];
+
+            // The definition has already been checked, so any `__VA_OPT__` here belongs to a variadic macro and has a group
+            if is_name(token, VA_OPT)
+            {
+                let end = va_opt_group_end(body, i).unwrap();
+                let mut group = self.va_opt_replacement(&body[i + 2..end], arguments)?;
+
+                match group.first_mut()
+                {
+                    Some(first) => first.leading_space = token.leading_space,
+                    None => pieces.push(Piece::Placemarker)
+                }
+
+                pieces.extend(group.into_iter().map(Piece::Token));
+                i = end + 1;
+                continue;
+            }
+
+            if token.is_punct(Punct::Hash) && i + 1 < body.len()
+            {
+                let next = &body[i + 1];
+
+                if is_name(next, VA_OPT)
+                {
+                    let end = va_opt_group_end(body, i + 1).unwrap();
+                    let group = self.va_opt_replacement(&body[i + 3..end], arguments)?;
+
+                    pieces.push(Piece::Token(self.stringify(&group, token)));
+                    i = end + 1;
+                    continue;
+                }
+
+                if let Some(index) = arguments.index(next)
+                {
+                    pieces.push(Piece::Token(self.stringify(&arguments.raw[index], token)));
+                    i += 2;
+                    continue;
+                }
+            }
+
+            // As a GNU extension, a comma pasted with the variable arguments is removed when they are empty
+            if token.is_punct(Punct::Comma) && is_paste(Some(i + 1)) && body.get(i + 2).is_some_and(|next| is_name(next, VA_ARGS))
+            {
+                if !arguments.variadic_empty()
+                {
+                    pieces.push(Piece::Token(token.clone()));
+                    pieces.extend(self.expanded_argument(arguments, arguments.raw.len() - 1)?.into_iter().map(Piece::Token));
+                }
+
+                i += 3;
+                continue;
+            }
+
+            if token.is_punct(Punct::HashHash)
+            {
+                pieces.push(Piece::Paste(token.clone()));
+            }
+            else if let Some(index) = arguments.index(token)
+            {
+                // Operands of `##` use the argument as written, otherwise it is completely replaced first
+                let mut argument = if is_paste(i.checked_sub(1)) || is_paste(Some(i + 1))
+                {
+                    arguments.raw[index].clone()
+                }
+                else
+                {
+                    self.expanded_argument(arguments, index)?
+                };
+
+                // The argument is spaced like the parameter it replaces
+                match argument.first_mut()
+                {
+                    Some(first) => first.leading_space = token.leading_space,
+                    None => pieces.push(Piece::Placemarker)
+                }
+
+                pieces.extend(argument.into_iter().map(Piece::Token));
+            }
+            else
+            {
+                pieces.push(Piece::Token(token.clone()));
+            }
+
+            i += 1;
+        }
+
+        Ok(pieces)
+    }
+
+    /// Replace the group of a `__VA_OPT__`, which is empty unless the variable arguments expand to some tokens
+    fn va_opt_replacement(&self, group: &[Token], arguments: &mut Arguments) -> CompilerResult<Vec<Token>>
+    {
+        if arguments.variadic_empty() || self.expanded_argument(arguments, arguments.raw.len() - 1)?.is_empty()
+        {
+            return Ok(Vec::new());
+        }
+
+        let pieces = self.substitution_pieces(group, arguments)?;
+
+        self.paste_pieces(pieces)
+    }
+
+    /// Perform the `##` operations in a substituted replacement, then remove any placemarkers
+    fn paste_pieces(&self, pieces: Vec<Piece>) -> CompilerResult<Vec<Token>>
+    {
+        // Pastes are performed from left to right, so the result of one paste can be the left operand of the next
+        let mut pasted: Vec<Piece> = Vec::new();
+        let mut pieces = pieces.into_iter();
+
+        while let Some(piece) = pieces.next()
+        {
+            let operator = match piece
+            {
+                Piece::Paste(operator) => operator,
+                piece =>
+                {
+                    pasted.push(piece);
+                    continue;
+                }
+            };
+
+            let piece = match (pasted.pop(), pieces.next())
+            {
+                (Some(Piece::Token(left)), Some(Piece::Token(right))) => Piece::Token(self.paste(&left, &right, &operator)?),
+                (Some(Piece::Placemarker), Some(right @ (Piece::Token(_) | Piece::Placemarker))) => right,
+                (Some(left), Some(Piece::Placemarker)) => left,
+                _ => return Err(PreprocessorError::syntax_error(format!("{} is missing an operand", operator.code_styled()), &operator).into())
+            };
+
+            pasted.push(piece);
+        }
+
+        Ok(pasted.into_iter().filter_map(|piece| match piece
+        {
+            Piece::Token(token) => Some(token),
+            _ => None
+        }).collect())
+    }
+
+    /// Expand the macro invocation starting with the given name, reading any further tokens it needs from the file
+    pub fn expand_macro<'x, I: Iterator<Item = &'x Token>>(&self, mut name: Token, file: &mut Peekable<I>) -> CompilerResult<Vec<Token>>
+    {
+        let comments = std::mem::take(&mut name.comments);
+        let invocation = name.get_original().clone();
+
+        let mut input = ExpansionInput { pending: VecDeque::from(vec![name]), file };
+        let mut expanded = self.rescan(&mut input)?;
+
+        // Arguments are part of the expansion too, wherever they were written
+        for token in &mut expanded
+        {
+            if token.original_location.is_none()
+            {
+                token.original_location = Some(invocation.clone());
+            }
+        }
+
+        // Comments before the invocation stay in front of its replacement
+        if let Some(first) = expanded.first_mut()
+        {
+            first.comments.splice(0..0, comments);
+        }
+
+        Ok(expanded)
+    }
+
+    /// Completely macro replace a sequence of tokens on its own
+    pub fn expand_tokens(&self, tokens: Vec<Token>) -> CompilerResult<Vec<Token>>
+    {
+        let mut file = std::iter::empty().peekable();
+        let mut input = ExpansionInput { pending: VecDeque::from(tokens), file: &mut file };
+
+        self.rescan(&mut input)
+    }
+
+    /// Copy the tokens of a macro body so that they refer back to where the macro was invoked
+    fn instantiate(&self, body: &[Token], invocation: &Token) -> Vec<Token>
+    {
+        body.iter().map(|token|
+        {
+            let mut token = token.clone();
+            token.original_location = Some(invocation.get_original().clone());
+            token.expansions = std::iter::once(self.expansion_frame(invocation)).chain(invocation.expansions.iter().cloned()).collect();
+            token
+        }).collect()
+    }
+
+    /// Describe the expansion of the macro named by the given token, for diagnostics
+    fn expansion_frame(&self, invocation: &Token) -> ExpansionFrame
+    {
+        let name = invocation.code_styled();
+
+        ExpansionFrame
+        {
+            definition: self.definition_locations.get(&name).cloned(),
+            invocation: invocation.location.clone(),
+            name
+        }
+    }
+
+    /// Replace macros in the pending tokens until none are left, rescanning each replacement along with what follows it
+    ///
+    /// This follows Prosser's algorithm: a token is never replaced by a macro in its hide set, and the tokens of a
+    /// replacement are hidden from the macro which produced them along with every macro the invocation came from.
+    fn rescan<'x, I: Iterator<Item = &'x Token>>(&self, input: &mut ExpansionInput<'_, 'x, I>) -> CompilerResult<Vec<Token>>
+    {
+        let mut output = Vec::new();
+
+        while let Some(token) = input.pending.pop_front()
+        {
+            let name = match &token.token_type
+            {
+                TokenType::Identifier(name) if !token.hide_set.contains(name) && self.is_defined(name) => name.clone(),
+                _ =>
+                {
+                    output.push(token);
+                    continue;
+                }
+            };
+
+            match &self.defines[&name]
+            {
+                MacroReplacements::None => {},
+                MacroReplacements::Dynamic(dynamic) =>
+                {
+                    let mut replacement = self.dynamic_replacement(*dynamic, &token);
+                    replacement.hide_set.insert(name);
+                    replacement.expansions.insert(0, self.expansion_frame(&token));
+                    input.push_expansion(vec![replacement]);
+                },
+                MacroReplacements::DirectReplacement(body) =>
+                {
+                    let mut hide_set = token.hide_set.clone();
+                    hide_set.insert(name);
+
+                    let replacement = self.substitute(&token, body, &mut Arguments::new(&[], &[]), &hide_set)?;
+                    input.push_expansion(replacement);
+                },
+                MacroReplacements::FunctionLikeReplacement(parameters, body) =>
+                {
+                    // The name of a function like macro on its own is left alone
+                    if !input.peek().is_some_and(|next| next.is_punct(Punct::LeftParen))
+                    {
+                        output.push(token);
+                        continue;
+                    }
+
+                    let variadic = is_variadic(parameters);
+                    let (mut arguments, right_paren) = input.collect_arguments(&token, Some(parameters.len()).filter(|_| variadic))?;
+
+                    // An empty argument list gives no arguments rather than a single empty one
+                    if parameters.is_empty() && arguments.len() == 1 && arguments[0].is_empty()
+                    {
+                        arguments.clear();
+                    }
+
+                    // The variable arguments may be left out entirely
+                    if variadic && arguments.len() == parameters.len() - 1
+                    {
+                        arguments.push(Vec::new());
+                    }
+
+                    if arguments.len() != parameters.len()
+                    {
+                        let expected = if variadic { format!("at least {}", parameters.len() - 1) } else { parameters.len().to_string() };
+
+                        return Err(PreprocessorError::syntax_error(format!("Macro {} expects {} arguments, but {} were given", name, expected, arguments.len()), &token).into());
+                    }
+
+                    // Only macros hiding both the name and the right paren stay hidden, since the invocation may have
+                    // only partly come from their replacements
+                    let mut hide_set = token.hide_set.intersection(&right_paren.hide_set).cloned().collect::<HashSet<_>>();
+                    hide_set.insert(name.clone());
+
+                    let replacement = self.substitute(&token, body, &mut Arguments::new(parameters, &arguments), &hide_set)?;
+                    input.push_expansion(replacement);
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::compiler::Compiler;
+    use crate::settings::CompilerSettings;
+    use crate::tokenizer::{FileManager, tokenize};
+
+    use super::*;
+
+    /// Preprocess some source and spell each token of the result as it would be written out
+    fn expand(source: &str) -> Vec<String>
+    {
+        let settings = CompilerSettings::default();
+        let mut compiler = Compiler::with_settings(&settings);
+        compiler.add_file(FileManager::from_text("<test>", String::from(source)));
+
+        let tokens = PreprocessorContext::with_compiler(&mut compiler).preprocess("<test>").ok().unwrap();
+
+        tokens.iter().filter(|token| !token.is_eof()).map(|token| token.source_spelling()).collect()
+    }
+
+    /// Spell each token of some text as it was written, which is compared against the result of expansion so that
+    /// spacing doesn't matter
+    fn spellings(text: &str) -> Vec<String>
+    {
+        let tokens = tokenize(&FileManager::from_text("<expected>", String::from(text)), &CompilerSettings::default()).ok().unwrap();
+
+        tokens.iter().filter(|token| !token.is_eof()).map(|token| token.source_spelling()).collect()
+    }
+
+    #[test]
+    fn rescanning_continues_into_the_file()
+    {
+        let source = "#define f(a) a*g\n#define g(a) f(a)\nf(2)(9)\n";
+
+        assert_eq!(expand(source), spellings("2*9*g"));
+    }
+
+    #[test]
+    fn macros_are_not_replaced_within_themselves()
+    {
+        let source = "#define x x\n#define f(a) f(x * (a))\nf(f(x))\n";
+
+        assert_eq!(expand(source), spellings("f(x * (f(x * (x))))"));
+    }
+
+    #[test]
+    fn standard_replacement_example()
+    {
+        let source = "\
+#define x 3
+#define f(a) f(x * (a))
+#undef x
+#define x 2
+#define g f
+#define z z[0]
+#define h g(~
+#define m(a) a(w)
+#define w 0,1
+#define t(a) a
+#define p() int
+#define q(x) x
+#define r(x,y) x ## y
+#define str(x) # x
+f(y+1) + f(f(z)) % t(t(g)(0) + t)(1);
+g(x+(3,4)-w) | h 5) & m
+(f)^m(m);
+p() i[q()] = { q(1), r(2,3), r(4,), r(,5), r(,) };
+char c[2][6] = { str(hello), str() };
+";
+
+        let expected = "\
+f(2 * (y+1)) + f(2 * (f(2 * (z[0])))) % f(2 * (0)) + t(1);
+f(2 * (2+(3,4)-0,1)) | f(2 * (~ 5)) & f(2 * (0,1))^m(0,1);
+int i[] = { 1, 23, 4, 5, };
+char c[2][6] = { \"hello\", \"\" };
+";
+
+        assert_eq!(expand(source), spellings(expected));
+    }
+
+    #[test]
+    fn standard_stringizing_example()
+    {
+        let source = "\
+#define str(s) # s
+#define xstr(s) str(s)
+#define debug(s, t) printf(\"x\" # s \"= %d, x\" # t \"= %s\", \\
+ x ## s, x ## t)
+#define glue(a, b) a ## b
+#define xglue(a, b) glue(a, b)
+#define HIGHLOW \"hello\"
+#define LOW LOW \", world\"
+#define foo 4
+debug(1, 2);
+fputs(str(strncmp(\"abc\\0d\", \"abc\", '\\4') // this goes away
+ == 0) str(: @\\n), s);
+glue(HIGH, LOW);
+xglue(HIGH, LOW)
+str(foo) xstr(foo)
+";
+
+        let expected = "\
+printf(\"x\" \"1\" \"= %d, x\" \"2\" \"= %s\", x1, x2);
+fputs(\"strncmp(\\\"abc\\\\0d\\\", \\\"abc\\\", '\\\\4') == 0\" \": @\\n\", s);
+\"hello\";
+\"hello\" \", world\"
+\"foo\" \"4\"
+";
+
+        assert_eq!(expand(source), spellings(expected));
+    }
+
+    #[test]
+    fn stringizing_keeps_literal_spellings()
+    {
+        let source = "#define S(x) #x\nS(\"\\xff\") S('\\101') S(u8\"\\0\" \\n)\n";
+
+        assert_eq!(expand(source), vec!["\"\\\"\\\\xff\\\"\"", "\"'\\\\101'\"", "\"u8\\\"\\\\0\\\" \\n\""]);
+    }
+}
@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use crate::errors::*;
+use crate::preprocessor::PreprocessorError;
+use crate::tokenizer::{Token, TokenType, Punct};
+
+use super::*;
+
+/// Deepest that includes may be nested, which stops a header that includes itself from recursing forever
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+/// A file which is being preprocessed
+#[derive(Debug, Clone)]
+pub struct IncludedFile
+{
+    pub filename: String,
+    /// Index of the include directory the file was found in, which is None for the main file and for headers found
+    /// next to the file which included them
+    pub directory: Option<usize>
+}
+
+/// An #include of one file by another
+#[derive(Debug, Clone)]
+pub struct IncludeEdge
+{
+    pub includer: String,
+    pub included: String,
+    /// True if the included file was found in a system include directory
+    pub system: bool
+}
+
+/// Name a file by its canonical path, so that different paths to the same file are recognized
+pub fn file_key(filename: &str) -> String
+{
+    std::fs::canonicalize(filename).map(|path| path.to_string_lossy().to_string()).unwrap_or_else(|_| filename.to_string())
+}
+
+/// Find the macro guarding a file whose contents are entirely wrapped in `#ifndef X`, `#define X` and `#endif`
+pub fn detect_include_guard(tokens: &[Token]) -> Option<String>
+{
+    let is_directive = |token: &Token, name: &str| matches!(&token.token_type, TokenType::PreprocessorDirective(directive) if directive == name);
+
+    // The guard has to open the file, and be defined straight away
+    let guard = match tokens
+    {
+        [ifndef, name, define, defined, ..] if is_directive(ifndef, "#ifndef") && name.is_identifier() &&
+            is_directive(define, "#define") && defined.token_type == name.token_type => name.code_styled(),
+        _ => return None
+    };
+
+    let mut depth = 0;
+
+    for (i, token) in tokens.iter().enumerate()
+    {
+        let directive = match &token.token_type
+        {
+            TokenType::PreprocessorDirective(directive) => directive.as_str(),
+            _ => continue
+        };
+
+        match directive
+        {
+            "#if" | "#ifdef" | "#ifndef" => depth += 1,
+            // Any other group in the outermost conditional means some of the file is kept when the guard is defined
+            "#else" | "#elif" | "#elifdef" | "#elifndef" if depth == 1 => return None,
+            "#endif" =>
+            {
+                depth -= 1;
+
+                // Only the rest of the endif line may follow the end of the guard
+                if depth == 0
+                {
+                    return tokens[i + 1..].iter().all(|token| token.is_eof() || !token.at_line_start).then_some(guard);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    None
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Directories searched for included headers in order, along with the index of the first one searched for names
+    /// in angle brackets
+    fn include_directories(&self) -> (Vec<&'b str>, usize)
+    {
+        let settings = self.compiler.settings();
+
+        let directories = settings.quote_directories.iter()
+            .chain(settings.include_directories.iter())
+            .chain(settings.system_directories.iter())
+            .map(|directory| directory.as_str())
+            .collect();
+
+        (directories, settings.quote_directories.len())
+    }
+
+    /// Check if the include directory with the given index holds system headers
+    fn is_system_directory(&self, index: usize) -> bool
+    {
+        let settings = self.compiler.settings();
+
+        index >= settings.quote_directories.len() + settings.include_directories.len()
+    }
+
+    /// Find an included header, returning its path and the index of the include directory it was found in
+    ///
+    /// Quoted names are first looked for next to the including file, then in the -iquote, -I and -isystem
+    /// directories. Names in angle brackets skip straight to the -I directories. Searches for #include_next start
+    /// after the directory the current file was found in.
+    pub fn search_include_paths(&self, filename: &str, angled: bool, next: bool) -> Option<(String, Option<usize>)>
+    {
+        if Path::new(filename).is_absolute()
+        {
+            return Path::new(filename).is_file().then(|| (filename.to_string(), None));
+        }
+
+        let (directories, angled_start) = self.include_directories();
+        let current = self.filename_stack.last();
+
+        let start = match current.and_then(|file| file.directory)
+        {
+            Some(index) if next => index + 1,
+            _ if next || angled => angled_start,
+            _ =>
+            {
+                if let Some(parent) = current.and_then(|file| Path::new(&file.filename).parent())
+                {
+                    let path = parent.join(filename);
+
+                    if path.is_file()
+                    {
+                        return Some((path.to_string_lossy().to_string(), None));
+                    }
+                }
+
+                0
+            }
+        };
+
+        directories.iter().enumerate().skip(start).find_map(|(index, directory)|
+        {
+            let path = Path::new(directory).join(filename);
+            path.is_file().then(|| (path.to_string_lossy().to_string(), Some(index)))
+        })
+    }
+
+    /// Preprocess an included file, leaving out its end of file token
+    pub fn include_file(&mut self, directive: &Token, filename: &str, angled: bool) -> CompilerResult<Vec<Token>>
+    {
+        let next = directive.code_styled() == "#include_next";
+
+        if self.filename_stack.len() >= MAX_INCLUDE_DEPTH
+        {
+            return Err(PreprocessorError::syntax_error(format!("Includes nested more than {} deep, a header may be including itself", MAX_INCLUDE_DEPTH), directive).into());
+        }
+
+        let (path, directory) = self.search_include_paths(filename, angled, next)
+            .ok_or_else(|| PreprocessorError::syntax_error(format!("Cannot find included file {}", filename), directive))?;
+
+        // Headers are dependencies of the files including them even if they end up being skipped
+        self.include_edges.push(IncludeEdge
+        {
+            includer: self.filename_stack.last().map(|file| file.filename.clone()).unwrap_or_default(),
+            included: path.clone(),
+            system: directory.is_some_and(|index| self.is_system_directory(index))
+        });
+
+        // Files which can only be included once, or whose guard is still defined, are skipped without being opened again
+        let key = file_key(&path);
+
+        if self.once_files.contains(&key) || self.include_guards.get(&key).is_some_and(|guard| self.is_defined(guard))
+        {
+            trace!("Skipping {}, which has already been included", path);
+            return Ok(Vec::new());
+        }
+
+        let mut tokens = self.preprocess_file(&path, directory)?;
+
+        // We need to make sure that the end of file token isn't passed along, though any comments on it are kept for
+        // the token after the directive
+        if let Some(eof) = tokens.pop_if(|token| token.is_eof())
+        {
+            self.pending_comments.extend(eof.comments);
+        }
+
+        Ok(tokens)
+    }
+
+    /// Get the name of an included header and whether it was in angle brackets
+    ///
+    /// Anything other than a single header name or string literal is macro replaced first, after which it must be a
+    /// string literal or a sequence of tokens between angle brackets.
+    pub fn header_name(&self, directive: &Token, tokens: Vec<Token>) -> CompilerResult<(String, bool)>
+    {
+        let tokens = match tokens.as_slice()
+        {
+            [token] if matches!(token.token_type, TokenType::HeaderName(_) | TokenType::StringLiteral(_)) => tokens,
+            _ => self.expand_tokens(tokens)?
+        };
+
+        match tokens.first().map(|token| &token.token_type)
+        {
+            Some(TokenType::HeaderName(name)) if tokens.len() == 1 => Ok((name.clone(), true)),
+            Some(TokenType::StringLiteral(literal)) if tokens.len() == 1 => Ok((literal.text(), false)),
+            Some(TokenType::Punct(Punct::LessThan)) if tokens.last().unwrap().is_punct(Punct::GreaterThan) && tokens.len() > 2 =>
+            {
+                Ok((Self::spell_tokens(&tokens[1..tokens.len() - 1]), true))
+            },
+            _ => Err(PreprocessorError::syntax_error(format!("Expected header name after {}", directive.code_styled()), tokens.first().unwrap_or(directive)).into())
+        }
+    }
+}
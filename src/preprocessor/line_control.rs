@@ -0,0 +1,121 @@
+use crate::errors::*;
+use crate::preprocessor::PreprocessorError;
+use crate::tokenizer::{Token, TokenType, Location};
+
+use super::*;
+
+/// Largest line number a #line directive may give
+const MAX_LINE_NUMBER: u64 = 2147483647;
+
+/// Where a #line directive moved the reported position of the lines after it
+#[derive(Debug, Clone)]
+pub struct LineMapping
+{
+    /// Reported line number of the line following the directive
+    pub line: usize,
+    pub filename: String
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Translate a location in a file into the location reported for it after any #line directives
+    pub fn presumed_location(&self, location: &Location) -> Location
+    {
+        let mapping = self.line_mappings.get(&location.filename).and_then(|mappings| mappings.range(..location.line).next_back());
+
+        match mapping
+        {
+            Some((directive_line, mapping)) =>
+            {
+                let mut presumed = location.clone();
+                presumed.filename = mapping.filename.clone();
+                presumed.line = mapping.line + (location.line - directive_line - 1);
+                presumed
+            },
+            None => location.clone()
+        }
+    }
+
+    /// Rewrite the locations of a token to those reported after any #line directives
+    pub fn presume_token(&self, token: &mut Token)
+    {
+        token.location = self.presumed_location(&token.location);
+        token.original_location = token.original_location.as_ref().map(|location| self.presumed_location(location));
+
+        for frame in &mut token.expansions
+        {
+            frame.invocation = self.presumed_location(&frame.invocation);
+            frame.definition = frame.definition.as_ref().map(|location| self.presumed_location(location));
+        }
+    }
+
+    /// Perform a #line directive, which sets the line number and optionally the filename reported for the next line
+    pub fn line_directive(&mut self, directive: &Token, tokens: Vec<Token>) -> CompilerResult<()>
+    {
+        // The line is only macro replaced if it isn't already in the expected form
+        let tokens = if tokens.first().is_some_and(|token| token.is_integer())
+        {
+            tokens
+        }
+        else
+        {
+            self.expand_tokens(tokens)?
+        };
+
+        let line = match tokens.first().map(|token| &token.token_type)
+        {
+            Some(TokenType::IntegerLiteral(integer)) if integer.spelling.chars().all(|c| c.is_ascii_digit()) => integer.value,
+            _ => return Err(PreprocessorError::syntax_error(format!("Expected a line number after {}", directive.code_styled()), tokens.first().unwrap_or(directive)).into())
+        };
+
+        if line == 0 || line > MAX_LINE_NUMBER
+        {
+            return Err(PreprocessorError::syntax_error(format!("Line number {} is out of range", line), &tokens[0]).into());
+        }
+
+        let filename = match tokens.get(1).map(|token| &token.token_type)
+        {
+            Some(TokenType::StringLiteral(literal)) if tokens.len() == 2 => Some(literal.text()),
+            None => None,
+            _ => return Err(PreprocessorError::syntax_error(format!("Expected a filename after the line number of {}", directive.code_styled()), &tokens[1]).into())
+        };
+
+        self.map_line(directive, line as usize, filename);
+
+        Ok(())
+    }
+
+    /// Perform a line marker of the form `# 12 "file.c" 1`, as written in preprocessed source
+    ///
+    /// The flags after the filename, which say whether a header is being entered or left, aren't needed.
+    pub fn line_marker(&mut self, directive: &Token, tokens: Vec<Token>) -> CompilerResult<()>
+    {
+        let line = directive.code_styled()[1..].parse::<u64>().ok().filter(|line| *line <= MAX_LINE_NUMBER)
+            .ok_or_else(|| PreprocessorError::syntax_error(format!("Line number {} is out of range", &directive.code_styled()[1..]), directive))?;
+
+        let filename = match tokens.first().map(|token| &token.token_type)
+        {
+            Some(TokenType::StringLiteral(literal)) => Some(literal.text()),
+            None => None,
+            _ => return Err(PreprocessorError::syntax_error(format!("Expected a filename after the line marker {}", directive.code_styled()), &tokens[0]).into())
+        };
+
+        if let Some(flag) = tokens.iter().skip(1).find(|token| !token.is_integer())
+        {
+            return Err(PreprocessorError::syntax_error(format!("Expected flags after the filename of line marker {}", directive.code_styled()), flag).into());
+        }
+
+        self.map_line(directive, line as usize, filename);
+
+        Ok(())
+    }
+
+    /// Report the line after a directive as the given line, in the given file or else the file it is already reported in
+    fn map_line(&mut self, directive: &Token, line: usize, filename: Option<String>)
+    {
+        let filename = filename.unwrap_or_else(|| self.presumed_location(&directive.location).filename);
+
+        self.line_mappings.entry(directive.location.filename.clone()).or_default()
+            .insert(directive.location.line, LineMapping { line, filename });
+    }
+}
@@ -1,5 +1,26 @@
+pub mod conditional;
+pub use conditional::*;
+
 pub mod error;
 pub use error::*;
 
+pub mod dependencies;
+
+pub mod expansion;
+
+pub mod include;
+pub use include::*;
+
+pub mod line_control;
+pub use line_control::*;
+
+pub mod output;
+
+pub mod pragma;
+pub use pragma::*;
+
+pub mod predefined;
+pub use predefined::*;
+
 pub mod preprocessor;
 pub use preprocessor::*;
\ No newline at end of file
@@ -0,0 +1,194 @@
+use crate::settings::CompilerSettings;
+use crate::tokenizer::{Token, Comment, Location, FileManager, tokenize, escape_literal};
+
+use super::*;
+use super::expansion::VA_ARGS;
+
+/// Most lines the preprocessed source skips with blank lines rather than a line marker
+const MAX_BLANK_LINES: usize = 8;
+
+/// Builds the preprocessed source, keeping track of where the line being written came from
+struct PreprocessedWriter<'s>
+{
+    settings: &'s CompilerSettings,
+    text: String,
+    /// File and line of the source which the current line of output came from
+    position: Option<(String, usize)>,
+    /// The last token written on the current line, if nothing has come after it
+    previous: Option<Token>,
+    at_line_start: bool,
+    /// Set once nothing more can be written on the current line, such as after a pragma
+    line_closed: bool
+}
+
+impl<'s> PreprocessedWriter<'s>
+{
+    /// Start a new line of output for something at the given location, unless it belongs on the current line
+    fn move_to(&mut self, location: &Location)
+    {
+        let same_file = self.position.as_ref().is_some_and(|(filename, _)| *filename == location.filename);
+        let line = self.position.as_ref().map(|(_, line)| *line).unwrap_or(0);
+        let same_line = same_file && location.line <= line;
+
+        // Tokens from earlier lines, such as those in macro arguments, are written on the current line
+        if same_line && !self.line_closed
+        {
+            return;
+        }
+
+        if !self.text.is_empty()
+        {
+            self.text.push('\n');
+        }
+
+        // Short gaps are kept as blank lines, anything else needs a line marker to give the position
+        if same_file && !same_line && location.line - line <= MAX_BLANK_LINES
+        {
+            for _ in line + 1..location.line
+            {
+                self.text.push('\n');
+            }
+        }
+        else if !self.settings.no_line_markers
+        {
+            self.text += &format!("# {} \"{}\"\n", location.line, escape_literal(&location.filename, '"'));
+        }
+
+        self.position = Some((location.filename.clone(), location.line));
+        self.previous = None;
+        self.at_line_start = true;
+        self.line_closed = false;
+
+        // Indentation is kept
+        self.text += &" ".repeat(location.column - 1);
+    }
+
+    /// Check if a token would be read as part of the token before it if they were written next to each other
+    fn would_paste(&self, token: &Token) -> bool
+    {
+        let previous = match &self.previous
+        {
+            Some(previous) => previous,
+            None => return false
+        };
+
+        // Tokens which were next to each other in the source can be written the same way
+        if previous.original_location.is_none() && token.original_location.is_none() &&
+            previous.location.filename == token.location.filename && previous.location.span.end == token.location.span.start
+        {
+            return false;
+        }
+
+        let spelling = format!("{}{}", previous.code_styled(), token.code_styled());
+
+        match tokenize(&FileManager::from_text("<output>", spelling), self.settings)
+        {
+            Ok(tokens) => !matches!(tokens.as_slice(), [first, second, _] if first.token_type == previous.token_type && second.token_type == token.token_type),
+            Err(_) => true
+        }
+    }
+
+    fn write_comment(&mut self, comment: &Comment)
+    {
+        self.move_to(&comment.location);
+
+        if !self.at_line_start
+        {
+            self.text.push(' ');
+        }
+
+        self.text += &comment.text;
+
+        // Block comments may span several lines
+        if let Some((_, line)) = &mut self.position
+        {
+            *line += comment.text.matches('\n').count();
+        }
+
+        self.previous = None;
+        self.at_line_start = false;
+    }
+
+    fn write_token(&mut self, token: &Token)
+    {
+        // Directives such as pragmas have to be on lines of their own
+        let directive = token.is_preprocessor_directive();
+        self.line_closed |= directive && !self.at_line_start;
+
+        self.move_to(token.get_original());
+
+        if !self.at_line_start && (token.leading_space || self.would_paste(token))
+        {
+            self.text.push(' ');
+        }
+
+        // Literals are written exactly as they were, their decoded values can't always be spelled the same way
+        self.text += &token.source_spelling();
+
+        self.previous = Some(token.clone());
+        self.at_line_start = false;
+        self.line_closed = directive;
+    }
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Write preprocessed tokens as C source, with line markers wherever the output moves to another file or skips
+    /// many lines
+    pub fn preprocessed_source(&self, tokens: &[Token]) -> String
+    {
+        let mut writer = PreprocessedWriter { settings: self.compiler.settings(), text: String::new(), position: None, previous: None, at_line_start: true, line_closed: false };
+
+        for token in tokens
+        {
+            for comment in &token.comments
+            {
+                writer.write_comment(comment);
+            }
+
+            if !token.is_eof()
+            {
+                writer.write_token(token);
+            }
+        }
+
+        if !writer.text.is_empty()
+        {
+            writer.text.push('\n');
+        }
+
+        writer.text
+    }
+
+    /// Write a #define for every macro which is defined, in order of name
+    pub fn macro_definitions(&self) -> String
+    {
+        let mut names = self.defines.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut text = String::new();
+
+        for name in names
+        {
+            let definition = match &self.defines[name]
+            {
+                MacroReplacements::None => name.clone(),
+                MacroReplacements::DirectReplacement(body) => format!("{} {}", name, Self::spell_tokens(body)),
+                MacroReplacements::FunctionLikeReplacement(parameters, body) =>
+                {
+                    let parameters = parameters.iter()
+                        .map(|parameter| if parameter.code_styled() == VA_ARGS { String::from("...") } else { parameter.code_styled() })
+                        .collect::<Vec<_>>();
+
+                    format!("{}({}) {}", name, parameters.join(","), Self::spell_tokens(body))
+                },
+                // __FILE__ and __LINE__ have no replacement of their own
+                MacroReplacements::Dynamic(_) => continue
+            };
+
+            text += &format!("#define {}\n", definition.trim_end());
+        }
+
+        text
+    }
+}
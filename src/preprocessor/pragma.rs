@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use crate::errors::*;
+use crate::preprocessor::PreprocessorError;
+use crate::tokenizer::{Token, TokenType, Punct, FileManager, tokenize};
+
+use super::*;
+
+/// Handles a pragma, given the pragma or _Pragma token and the tokens after the pragma's name
+type PragmaHandler = fn(&mut PreprocessorContext, &Token, &[Token]) -> CompilerResult<()>;
+
+/// Pragmas which are understood, keyed by their first token
+const PRAGMAS: [(&str, PragmaHandler); 4] =
+[
+    ("once", pragma_once),
+    ("pack", pragma_pack),
+    ("GCC", pragma_gcc),
+    ("STDC", pragma_stdc)
+];
+
+/// Standard pragmas controlling floating point behavior, which are accepted but have no effect on integer code
+const STDC_PRAGMAS: [&str; 5] = ["FP_CONTRACT", "FENV_ACCESS", "CX_LIMITED_RANGE", "FENV_ROUND", "FENV_DEC_ROUND"];
+
+/// State which pragmas can change
+#[derive(Debug, Clone, Default)]
+pub struct PragmaState
+{
+    /// Warning options which are currently not reported
+    pub ignored_warnings: HashSet<String>,
+    /// Ignored warnings saved by `GCC diagnostic push`
+    pub diagnostic_stack: Vec<HashSet<String>>
+}
+
+/// `#pragma once` marks the current file so that it is never included again
+fn pragma_once(context: &mut PreprocessorContext, pragma: &Token, arguments: &[Token]) -> CompilerResult<()>
+{
+    if !arguments.is_empty()
+    {
+        context.warning("-Wpragmas", String::from("Extra tokens after #pragma once"), pragma);
+    }
+
+    if let Some(file) = context.filename_stack.last()
+    {
+        context.once_files.insert(file_key(&file.filename));
+    }
+
+    Ok(())
+}
+
+/// `#pragma pack` would set the alignment of structure members, but structures aren't laid out yet, so it is only
+/// checked and reported as having no effect
+fn pragma_pack(context: &mut PreprocessorContext, pragma: &Token, arguments: &[Token]) -> CompilerResult<()>
+{
+    let spellings = arguments.iter().map(|token| token.code_styled()).collect::<Vec<_>>();
+    let spellings = spellings.iter().map(|spelling| spelling.as_str()).collect::<Vec<_>>();
+
+    // Alignments must be small powers of two
+    let alignment = |spelling: &str| spelling.parse::<usize>().ok().filter(|n| n.is_power_of_two() && *n <= 16);
+
+    let well_formed = match spellings.as_slice()
+    {
+        ["(", ")"] | ["(", "push", ")"] | ["(", "pop", ")"] => true,
+        ["(", n, ")"] | ["(", "push", ",", n, ")"] => alignment(n).is_some(),
+        _ => false
+    };
+
+    let spelling = PreprocessorContext::spell_tokens(arguments);
+
+    if well_formed
+    {
+        context.warning("-Wpragmas", format!("Ignoring #pragma pack{}, which has no effect yet", spelling), pragma);
+    }
+    else
+    {
+        context.warning("-Wpragmas", format!("Malformed #pragma pack{}", spelling), pragma);
+    }
+
+    Ok(())
+}
+
+/// `#pragma GCC diagnostic` controls which warnings are reported
+fn pragma_gcc(context: &mut PreprocessorContext, pragma: &Token, arguments: &[Token]) -> CompilerResult<()>
+{
+    let spellings = arguments.iter().map(|token| token.code_styled()).collect::<Vec<_>>();
+    let option = arguments.get(2).and_then(|token| match &token.token_type
+    {
+        TokenType::StringLiteral(literal) => Some(literal.text()),
+        _ => None
+    });
+
+    let state = &mut context.pragmas;
+
+    match (spellings.get(1).map(|spelling| spelling.as_str()), option)
+    {
+        _ if spellings.first().map(|spelling| spelling.as_str()) != Some("diagnostic") =>
+        {
+            context.warning("-Wunknown-pragmas", format!("Ignoring unknown pragma GCC {}", PreprocessorContext::spell_tokens(arguments)), pragma);
+        },
+        (Some("push"), _) => state.diagnostic_stack.push(state.ignored_warnings.clone()),
+        (Some("pop"), _) =>
+        {
+            if let Some(ignored) = state.diagnostic_stack.pop()
+            {
+                state.ignored_warnings = ignored;
+            }
+        },
+        (Some("ignored"), Some(option)) =>
+        {
+            state.ignored_warnings.insert(option);
+        },
+        (Some("warning") | Some("error"), Some(option)) =>
+        {
+            state.ignored_warnings.remove(&option);
+        },
+        _ =>
+        {
+            context.warning("-Wpragmas", format!("Malformed #pragma GCC {}", PreprocessorContext::spell_tokens(arguments)), pragma);
+        }
+    }
+
+    Ok(())
+}
+
+/// `#pragma STDC` controls floating point behavior, which the code generator doesn't produce, so it is only checked
+fn pragma_stdc(context: &mut PreprocessorContext, pragma: &Token, arguments: &[Token]) -> CompilerResult<()>
+{
+    let name = arguments.first().map(|token| token.code_styled()).unwrap_or_default();
+
+    if !STDC_PRAGMAS.contains(&name.as_str())
+    {
+        context.warning("-Wunknown-pragmas", format!("Ignoring unknown pragma STDC {}", PreprocessorContext::spell_tokens(arguments)), pragma);
+    }
+    else if arguments.len() != 2
+    {
+        context.warning("-Wpragmas", format!("Malformed #pragma STDC {}", PreprocessorContext::spell_tokens(arguments)), pragma);
+    }
+
+    Ok(())
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Report a diagnostic which doesn't stop compilation, unless the warning option it belongs to is being ignored
+    pub fn warning(&mut self, option: &str, message: String, token: &Token)
+    {
+        if self.pragmas.ignored_warnings.contains(option)
+        {
+            return;
+        }
+
+        let location = self.presumed_location(token.get_original());
+
+        eprintln!("Preprocessor warning: {} [{}] at {}", message, option, location);
+
+        if let Ok(file) = self.compiler.get_file_manager(&location.filename)
+        {
+            file.display_arrow(&location);
+        }
+    }
+
+    /// Perform a pragma, given the tokens which followed #pragma
+    ///
+    /// When only preprocessing, every pragma except `#pragma once` is also meant for the compiler which reads the
+    /// output, so it is given back as a directive token to be written out.
+    pub fn pragma(&mut self, pragma: &Token, tokens: Vec<Token>) -> CompilerResult<Option<Token>>
+    {
+        let name = tokens.first().map(|token| token.code_styled());
+
+        match PRAGMAS.iter().find(|(pragma_name, _)| Some(pragma_name.to_string()) == name)
+        {
+            Some((_, handler)) => handler(self, pragma, &tokens[1..])?,
+            None => self.warning("-Wunknown-pragmas", format!("Ignoring unknown pragma {}", Self::spell_tokens(&tokens)), pragma)
+        }
+
+        if !self.compiler.settings().preprocess_only || name.as_deref() == Some("once")
+        {
+            return Ok(None);
+        }
+
+        let mut directive = pragma.clone();
+        directive.token_type = TokenType::PreprocessorDirective(format!("#pragma {}", Self::spell_tokens(&tokens)));
+
+        Ok(Some(directive))
+    }
+
+    /// Perform a `_Pragma("...")` operator, taking its operand from the given tokens
+    pub fn pragma_operator<'x, I: Iterator<Item = &'x Token>>(&mut self, operator: &Token, operand: &mut I) -> CompilerResult<Option<Token>>
+    {
+        PreprocessorError::expect_punct(operand.next(), Punct::LeftParen)?;
+
+        let literal = PreprocessorError::prevent_eof(operand.next())?;
+
+        let text = match &literal.token_type
+        {
+            TokenType::StringLiteral(text) => text.text(),
+            _ => return Err(PreprocessorError::syntax_error(format!("Expected string literal in {}, got {}", operator.code_styled(), literal.code_styled()), &literal).into())
+        };
+
+        PreprocessorError::expect_punct(operand.next(), Punct::RightParen)?;
+
+        // The string is lexed again and performed as the tokens of a #pragma directive
+        let mut tokens = tokenize(&FileManager::from_text("<_Pragma>", text), self.compiler.settings())?;
+        tokens.pop();
+
+        self.pragma(operator, tokens)
+    }
+
+    /// Perform any `_Pragma` operators in a sequence of tokens, removing them
+    pub fn apply_pragma_operators(&mut self, tokens: Vec<Token>) -> CompilerResult<Vec<Token>>
+    {
+        let mut result = Vec::new();
+        let mut iterator = tokens.iter();
+
+        while let Some(token) = iterator.next()
+        {
+            if matches!(&token.token_type, TokenType::Identifier(name) if name == "_Pragma")
+            {
+                result.extend(self.pragma_operator(token, &mut iterator)?);
+            }
+            else
+            {
+                result.push(token.clone());
+            }
+        }
+
+        Ok(result)
+    }
+}
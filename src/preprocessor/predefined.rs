@@ -0,0 +1,170 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::errors::*;
+use crate::parser::RawValueType;
+use crate::settings::MacroOption;
+use crate::tokenizer::{Token, TokenType, TextLiteral, EncodingPrefix, IntegerConstant, IntegerSuffix, FileManager, tokenize};
+
+use super::*;
+
+/// Macros whose replacement depends on where they are expanded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicMacro
+{
+    File,
+    Line
+}
+
+/// Month abbreviations in the format used by `__DATE__`
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Largest value of a signed integer type
+fn signed_max(value_type: RawValueType) -> u64
+{
+    (1u64 << (value_type.size() * 8 - 1)) - 1
+}
+
+/// Spell the date and time of translation as `__DATE__` and `__TIME__` expect, honoring SOURCE_DATE_EPOCH for
+/// reproducible builds
+fn translation_date_time() -> (String, String)
+{
+    let seconds = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+
+    // Convert the days since the epoch into a civil date
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let time = seconds % 86400;
+
+    (format!("\"{} {:>2} {}\"", MONTHS[month as usize - 1], day, year),
+     format!("\"{:02}:{:02}:{:02}\"", time / 3600, time / 60 % 60, time % 60))
+}
+
+impl<'a, 'b> PreprocessorContext<'a, 'b>
+{
+    /// Define the macros which every translation unit starts with
+    pub fn define_predefined_macros(&mut self) -> CompilerResult<()>
+    {
+        let target = self.compiler.settings().target;
+        let standard = self.compiler.settings().standard;
+        let (date, time) = translation_date_time();
+
+        let predefined =
+        [
+            ("__STDC__", String::from("1")),
+            ("__STDC_VERSION__", String::from(standard.version())),
+            ("__STDC_HOSTED__", String::from("0")),
+            ("__STDC_UTF_16__", String::from("1")),
+            ("__STDC_UTF_32__", String::from("1")),
+            ("__DATE__", date),
+            ("__TIME__", time),
+
+            // Compiler and target identification
+            ("__qorcc__", String::from("1")),
+            ("__riscv", String::from("1")),
+            ("__riscv_xlen", target.xlen().to_string()),
+            ("__riscv_mul", String::from("1")),
+            ("__riscv_div", String::from("1")),
+
+            // Type sizes follow the code generator
+            ("__CHAR_BIT__", String::from("8")),
+            ("__SIZEOF_POINTER__", target.pointer_size().to_string()),
+            ("__SIZEOF_SHORT__", RawValueType::I16.size().to_string()),
+            ("__SIZEOF_INT__", RawValueType::I32.size().to_string()),
+            ("__SIZEOF_LONG__", RawValueType::I64.size().to_string()),
+            ("__SIZEOF_LONG_LONG__", RawValueType::I64.size().to_string()),
+            ("__SCHAR_MAX__", signed_max(RawValueType::I8).to_string()),
+            ("__SHRT_MAX__", signed_max(RawValueType::I16).to_string()),
+            ("__INT_MAX__", signed_max(RawValueType::I32).to_string()),
+            ("__LONG_MAX__", format!("{}L", signed_max(RawValueType::I64))),
+            ("__LONG_LONG_MAX__", format!("{}LL", signed_max(RawValueType::I64)))
+        ];
+
+        // All of the replacements are lexed together, one per line
+        let text = predefined.iter().map(|(_, replacement)| format!("{}\n", replacement)).collect::<String>();
+        let mut tokens = tokenize(&FileManager::from_text("<built-in>", text), self.compiler.settings())?.into_iter().peekable();
+
+        for (line, (name, _)) in predefined.iter().enumerate()
+        {
+            let mut replacement = Vec::new();
+
+            while let Some(token) = tokens.next_if(|token| token.location.line == line + 1 && !token.is_eof())
+            {
+                replacement.push(token);
+            }
+
+            self.register_direct_replace_macro(name.to_string(), replacement);
+        }
+
+        self.defines.insert(String::from("__FILE__"), MacroReplacements::Dynamic(DynamicMacro::File));
+        self.defines.insert(String::from("__LINE__"), MacroReplacements::Dynamic(DynamicMacro::Line));
+
+        Ok(())
+    }
+
+    /// Apply the -D and -U options from the command line, in the order they were given
+    pub fn define_command_line_macros(&mut self) -> CompilerResult<()>
+    {
+        let options = &self.compiler.settings().macro_options;
+
+        if options.is_empty()
+        {
+            return Ok(());
+        }
+
+        // Each option becomes a directive, so definitions may be function like just as in a file
+        let text = options.iter().map(|option| match option
+        {
+            MacroOption::Define(definition) => match definition.split_once('=')
+            {
+                Some((name, value)) => format!("#define {} {}\n", name, value),
+                None => format!("#define {} 1\n", definition)
+            },
+            MacroOption::Undefine(name) => format!("#undef {}\n", name)
+        }).collect::<String>();
+
+        let tokens = tokenize(&FileManager::from_text("<command-line>", text), self.compiler.settings())?;
+        self.preprocess_tokens(tokens.iter())?;
+
+        Ok(())
+    }
+
+    /// Produce the replacement of a dynamic macro at the place it was invoked
+    pub fn dynamic_replacement(&self, dynamic: DynamicMacro, invocation: &Token) -> Token
+    {
+        let location = self.presumed_location(invocation.get_original());
+
+        let token_type = match dynamic
+        {
+            DynamicMacro::File =>
+            {
+                let mut units = Vec::new();
+
+                for c in location.filename.chars()
+                {
+                    EncodingPrefix::None.encode(c, &mut units);
+                }
+
+                TokenType::StringLiteral(TextLiteral { prefix: EncodingPrefix::None, units, source: None })
+            },
+            DynamicMacro::Line =>
+            {
+                let line = location.line as u64;
+
+                TokenType::IntegerLiteral(IntegerConstant { value: line, decimal: true, suffix: IntegerSuffix::None, spelling: line.to_string() })
+            }
+        };
+
+        let mut token = invocation.clone();
+        token.token_type = token_type;
+        token
+    }
+}
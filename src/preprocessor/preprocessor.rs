@@ -1,10 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::compiler::Compiler;
 use crate::preprocessor::PreprocessorError;
-use crate::tokenizer::Token;
+use crate::tokenizer::{Token, Comment, Location};
 use crate::errors::*;
 use crate::tokenizer::TokenType;
+use crate::tokenizer::Punct;
+
+use super::Conditional;
+use super::{IncludedFile, IncludeEdge, LineMapping, PragmaState, detect_include_guard, file_key};
+use super::DynamicMacro;
+use super::expansion::{VA_ARGS, VA_OPT};
+
+/// Directives which take part in conditional inclusion, and so are still looked at inside skipped groups
+const CONDITIONAL_DIRECTIVES: [&str; 8] = ["#if", "#ifdef", "#ifndef", "#elif", "#elifdef", "#elifndef", "#else", "#endif"];
 
 /// Macro Replacements
 #[derive(Debug, Clone)]
@@ -12,16 +21,32 @@ pub enum MacroReplacements
 {
     None,
     DirectReplacement(Vec<Token>),
-    FunctionLikeReplacement(Vec<Token>, Vec<Token>)
+    /// The parameters and body of a function like macro, a variadic macro's last parameter is `__VA_ARGS__`
+    FunctionLikeReplacement(Vec<Token>, Vec<Token>),
+    /// A predefined macro whose replacement depends on where it is expanded
+    Dynamic(DynamicMacro)
 }
 
 /// Preprocessor Context
 pub struct PreprocessorContext<'a, 'b>
 {
-    compiler: &'a mut Compiler<'b>,
-    defines: HashMap<String, MacroReplacements>,
-    filename_stack: Vec<String>,
-    if_stack: Vec<bool>
+    pub(super) compiler: &'a mut Compiler<'b>,
+    pub(super) defines: HashMap<String, MacroReplacements>,
+    /// Where each macro defined by a #define was defined
+    pub(super) definition_locations: HashMap<String, Location>,
+    pub(super) filename_stack: Vec<IncludedFile>,
+    /// Files which were marked with #pragma once
+    pub(super) once_files: HashSet<String>,
+    /// Macros guarding files whose contents are entirely within an #ifndef group
+    pub(super) include_guards: HashMap<String, String>,
+    /// Every #include performed, in order, including those of files which were skipped
+    pub(super) include_edges: Vec<IncludeEdge>,
+    /// Line numbers and filenames set by #line directives, by the file and line of each directive
+    pub(super) line_mappings: HashMap<String, BTreeMap<usize, LineMapping>>,
+    pub(super) pragmas: PragmaState,
+    /// Comments from before directives, which are output in front of the next token
+    pub(super) pending_comments: Vec<Comment>,
+    pub(super) if_stack: Vec<Conditional>
 }
 
 impl<'a, 'b> PreprocessorContext<'a, 'b>
@@ -32,7 +57,14 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
         {
             compiler,
             defines: HashMap::new(),
+            definition_locations: HashMap::new(),
             filename_stack: Vec::new(),
+            once_files: HashSet::new(),
+            include_guards: HashMap::new(),
+            include_edges: Vec::new(),
+            line_mappings: HashMap::new(),
+            pragmas: PragmaState::default(),
+            pending_comments: Vec::new(),
             if_stack: Vec::new()
         }
     }
@@ -47,61 +79,69 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
     /// Undefine an identifier
     pub fn undefine(&mut self, identifier: &str)
     {
+        self.definition_locations.remove(identifier);
+
         if self.defines.remove(identifier).is_none()
         {
             warn!("Undefining {}, which is not defined", identifier);
         }
     }
 
-    /// Find the filename in the given context
-    pub fn search_include_paths(&self, filename: &str) -> CompilerResult<String>
+    /// Preprocess a file into a sequence of tokens, and repeat until no transformation is performed
+    pub fn preprocess(&mut self, filename: &str) -> CompilerResult<Vec<Token>>
     {
-        if let Some(Some(last_path)) = self.filename_stack.last().map(|v| std::path::Path::new(v).parent())
-        {
-            let p = std::path::Path::new(last_path.to_str().unwrap());
-            let this_path = std::path::Path::join(p, filename);
+        let mut tokens = self.preprocess_file(filename, None)?;
 
-            if this_path.exists()
+        // Tokens report the locations given by any #line directives
+        if !self.line_mappings.is_empty()
+        {
+            for token in &mut tokens
             {
-                return Ok(this_path.to_str().unwrap().to_string());
+                self.presume_token(token);
             }
         }
 
-        Ok(filename.to_string())
-    }
-
-    /// Include a file
-    pub fn include_file(&mut self, filename: &str) -> CompilerResult<Vec<Token>>
-    {
-        let path = self.search_include_paths(filename)?;
-        
-        let v = self.preprocess(&path)?;
-
-        // We need to make sure that the end of file token isn't passed along
-        let final_tokens = v.iter().map_while(|v| if !v.is_eof() { Some(v.clone()) } else { None }).collect::<Vec<Token>>();
-
-        Ok(final_tokens)
+        Ok(tokens)
     }
 
-    /// Preprocess a file into a sequence of tokens, and repeat until no transformation is performed
-    pub fn preprocess(&mut self, filename: &str) -> CompilerResult<Vec<Token>>
+    /// Preprocess a file which was found in the given include directory
+    pub fn preprocess_file(&mut self, filename: &str, directory: Option<usize>) -> CompilerResult<Vec<Token>>
     {
         trace!("Preprocessing file {}", filename);
 
-        // Make sure the file is tokenized
-        self.compiler.get_file_manager(filename)?.tokenize()?;
+        // Make sure the file is tokenized, which only needs to happen the first time it is seen
+        let settings = self.compiler.settings();
+        let file = self.compiler.get_file_manager(filename)?;
+
+        if file.tokens.is_none()
+        {
+            file.tokenize(settings)?;
+        }
 
         // Get the FileManager for that file
-        let file = self.compiler.get_file_manager(filename)?.clone();
-        self.filename_stack.push(filename.to_string());
+        let file = file.clone();
+        self.filename_stack.push(IncludedFile { filename: filename.to_string(), directory });
+
+        // Conditionals must be closed in the same file that opened them
+        let conditional_depth = self.if_stack.len();
 
         let result = self.preprocess_tokens(file.iter())?;
 
-        if self.filename_stack.pop() != Some(filename.to_string())
+        if let Some(conditional) = self.if_stack.get(conditional_depth)
+        {
+            return Err(PreprocessorError::syntax_error(format!("Unterminated {} directive", conditional.directive.code_styled()), &conditional.directive).into());
+        }
+
+        if self.filename_stack.pop().map(|file| file.filename) != Some(filename.to_string())
         {
             unreachable!()
         }
 
+        if let Some(guard) = file.tokens.as_deref().and_then(detect_include_guard)
+        {
+            self.include_guards.insert(file_key(filename), guard);
+        }
+
         Ok(result)
     }
 
@@ -113,17 +153,12 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
 
         while let Some(mut peeked_next) = peekable_iter.peek()
         {
-            let current_line = peeked_next.location.line;
-            if !self.if_stack.iter().all(|v| *v)
+            // Skip over groups which aren't kept until the next directive which could change that
+            if !self.is_active()
             {
-                loop
+                while let Some(next) = peekable_iter.peek()
                 {
-                    let next = PreprocessorError::prevent_eof(peekable_iter.peek().map(|v| *v))?;
-
-                    if next.token_type == TokenType::PreprocessorDirective(String::from("#ifdef")) ||
-                       next.token_type == TokenType::PreprocessorDirective(String::from("#ifndef")) ||
-                       next.token_type == TokenType::PreprocessorDirective(String::from("#else")) ||
-                       next.token_type == TokenType::PreprocessorDirective(String::from("#endif"))
+                    if next.is_eof() || (next.is_preprocessor_directive() && CONDITIONAL_DIRECTIVES.contains(&next.code_styled().as_str()))
                     {
                         break;
                     }
@@ -131,11 +166,21 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
                     peekable_iter.next();
                 }
 
-                peeked_next = peekable_iter.peek().unwrap();
+                peeked_next = match peekable_iter.peek()
+                {
+                    Some(next) => next,
+                    None => break
+                };
             }
 
             if let TokenType::PreprocessorDirective(directive) = &peeked_next.token_type
             {
+                // Comments in groups which are skipped are dropped along with everything else
+                if self.is_active()
+                {
+                    self.pending_comments.extend(peeked_next.comments.iter().cloned());
+                }
+
                 match directive.as_str()
                 {
                     "#define" =>
@@ -144,51 +189,74 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
                         peekable_iter.next();
 
                         // Get the identifier name
-                        let identifier = PreprocessorError::expect_identifier(peekable_iter.next())?.code_styled();
+                        let name_token = PreprocessorError::expect_identifier(peekable_iter.next())?;
+                        let identifier = name_token.code_styled();
 
-                        // Determine the sequence of tokens which this expands to by finding the tokens remaining on the line
-                        let mut macro_tokens = Vec::new();
-                        while let Some(t) = peekable_iter.peek()
-                        {
-                            if t.is_eof() { break; }
-                            if t.location.line == current_line
-                            {
-                                macro_tokens.push(peekable_iter.next().unwrap().clone());
-                            }
-                            else
-                            {
-                                break;
-                            }
-                        }
+                        self.definition_locations.insert(identifier.clone(), name_token.location.clone());
+
+                        // Determine the sequence of tokens which this expands to by finding the tokens remaining on the logical line
+                        let macro_tokens = Self::rest_of_line(&mut peekable_iter);
 
                         // If there are no tokens remaining in the line, we are just defining the macro
-                        if macro_tokens.len() == 0
+                        if macro_tokens.is_empty()
                         {
                             self.register_macro_empty(identifier);
                         }
-                        // If the sequence begins with a left paren, then we are starting a function like macro
-                        else if TokenType::Symbol(String::from("(")) == macro_tokens[0].token_type
+                        // If the sequence begins with a left paren directly after the name, then we are starting a function like macro
+                        else if macro_tokens[0].is_punct(Punct::LeftParen) && !macro_tokens[0].leading_space
                         {
                             let mut peekable_iter = macro_tokens[1..].iter().peekable();
 
-                            let mut arguments = Vec::new();
+                            let mut arguments: Vec<Token> = Vec::new();
 
-                            loop
-                            {
-                                // Take the next token, which should be an identifier, and add it to the list of arguments
-                                arguments.push(PreprocessorError::expect_identifier(peekable_iter.next())?);
+                            let unterminated = || PreprocessorError::syntax_error(format!("Unterminated parameter list in definition of macro {}", identifier), &name_token);
 
-                                // Now we have a choice
-                                let next_token = PreprocessorError::prevent_eof(peekable_iter.next())?;
-                                // If the next symbol is a close paren, break out and use the remainder of the iterator as the pattern
-                                if TokenType::Symbol(String::from(")")) == next_token.token_type
-                                {
-                                    break;
-                                }
-                                // Otherwise, it must be a comma, in which case we will loop back to the top
-                                else
+                            // A macro may take no parameters at all
+                            if peekable_iter.peek().is_some_and(|t| t.is_punct(Punct::RightParen))
+                            {
+                                peekable_iter.next();
+                            }
+                            else
+                            {
+                                loop
                                 {
-                                    PreprocessorError::expect_symbol(Some(&next_token.clone()), ",")?;
+                                    let next_token = peekable_iter.next().ok_or_else(unterminated)?;
+
+                                    // An ellipsis stands for the variable arguments, and must be the last parameter
+                                    if next_token.is_punct(Punct::Ellipsis)
+                                    {
+                                        arguments.push(Token::construct(TokenType::Identifier(String::from(VA_ARGS)), next_token.location.clone()));
+                                        PreprocessorError::expect_punct(Some(peekable_iter.next().ok_or_else(unterminated)?), Punct::RightParen)?;
+                                        break;
+                                    }
+
+                                    // Take the next token, which should be an identifier, and add it to the list of arguments
+                                    let argument = PreprocessorError::expect_identifier(Some(next_token))?;
+
+                                    if argument.code_styled() == VA_ARGS || argument.code_styled() == VA_OPT
+                                    {
+                                        return Err(PreprocessorError::syntax_error(format!("{} cannot be used as a macro parameter", argument.code_styled()), &argument).into());
+                                    }
+
+                                    if arguments.iter().any(|a| a.token_type == argument.token_type)
+                                    {
+                                        return Err(PreprocessorError::syntax_error(format!("Duplicate macro parameter {}", argument.code_styled()), &argument).into());
+                                    }
+
+                                    arguments.push(argument);
+
+                                    // Now we have a choice
+                                    let next_token = peekable_iter.next().ok_or_else(unterminated)?;
+                                    // If the next symbol is a close paren, break out and use the remainder of the iterator as the pattern
+                                    if TokenType::Punct(Punct::RightParen) == next_token.token_type
+                                    {
+                                        break;
+                                    }
+                                    // Otherwise, it must be a comma, in which case we will loop back to the top
+                                    else
+                                    {
+                                        PreprocessorError::expect_punct(Some(next_token), Punct::Comma)?;
+                                    }
                                 }
                             }
 
@@ -199,31 +267,56 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
                                 pattern.push(token.clone());
                             }
 
+                            Self::check_replacement_list(&identifier, Some(&arguments), &pattern)?;
                             self.register_function_like_macro(identifier, arguments, pattern);
                         }
                         // Otherwise, we will register the sequence of tokens as the macro
                         else
                         {
+                            Self::check_replacement_list(&identifier, None, &macro_tokens)?;
                             self.register_direct_replace_macro(identifier, macro_tokens);
                         }
                     },
-                    "#include" =>
+                    "#include" | "#include_next" =>
                     {
-                        // Step to the next symbol
-                        peekable_iter.next();
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
 
-                        // Next there should be a string literal with the name of the imported file
-                        let filename = PreprocessorError::expect_string_literal(peekable_iter.next())?;
+                        // The rest of the line names the included file, possibly after macro replacement
+                        let (filename, angled) = self.header_name(&directive, line)?;
 
                         // Include the file by adding it to the file processing stack
-                        if let TokenType::StringLiteral(filename) = filename.token_type
-                        {
-                            result.append(&mut self.include_file(&filename)?);
-                        }
-                        else
-                        {
-                            unreachable!()
-                        }
+                        result.append(&mut self.include_file(&directive, &filename, angled)?);
+                    },
+                    "#pragma" =>
+                    {
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
+
+                        result.extend(self.pragma(&directive, line)?);
+                    },
+                    "#error" =>
+                    {
+                        let mut directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
+
+                        self.presume_token(&mut directive);
+
+                        return Err(PreprocessorError::error_directive(Self::spell_tokens(&line), &directive).into());
+                    },
+                    "#warning" =>
+                    {
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
+
+                        self.warning("-Wcpp", format!("#warning {}", Self::spell_tokens(&line)), &directive);
+                    },
+                    "#line" =>
+                    {
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
+
+                        self.line_directive(&directive, line)?;
                     },
                     "#undef" =>
                     {
@@ -236,92 +329,166 @@ impl<'a, 'b> PreprocessorContext<'a, 'b>
                         self.undefine(&identifier);
 
                     },
-                    "#ifdef" =>
+                    "#if" | "#ifdef" | "#ifndef" =>
                     {
-                        // Step to the next symbol
-                        peekable_iter.next();
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
 
-                        // Get the identifier name
-                        let identifier = PreprocessorError::expect_identifier(peekable_iter.next())?.code_styled();
-                    
-                        // Push the identifier being defined to the if stack
-                        self.if_stack.push(self.is_defined(&identifier));
+                        // Conditions inside skipped groups are never evaluated
+                        let enclosing_active = self.is_active();
+                        let condition = enclosing_active && self.directive_condition(&directive, line)?;
+
+                        self.if_stack.push(Conditional::new(directive, enclosing_active, condition));
                     },
-                    "#ifndef" =>
+                    "#elif" | "#elifdef" | "#elifndef" =>
                     {
-                        // Step to the next symbol
-                        peekable_iter.next();
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
 
-                        // Get the identifier name
-                        let identifier = PreprocessorError::expect_identifier(peekable_iter.next())?.code_styled();
+                        let (taken, seen_else) = match self.if_stack.last()
+                        {
+                            Some(conditional) => (conditional.taken, conditional.seen_else),
+                            None => return Err(PreprocessorError::syntax_error(format!("{} directive cannot be used outside of a preprocessor if statement", directive.code_styled()), &directive).into())
+                        };
+
+                        if seen_else
+                        {
+                            return Err(PreprocessorError::syntax_error(format!("{} directive cannot follow an else directive", directive.code_styled()), &directive).into());
+                        }
 
-                        // Push the inverse of the identifier being defined to the if stack
-                        self.if_stack.push(!self.is_defined(&identifier));
+                        // Once a group has been kept, the rest of the chain is skipped without being evaluated
+                        let condition = !taken && self.directive_condition(&directive, line)?;
+
+                        let conditional = self.if_stack.last_mut().unwrap();
+                        conditional.active = condition;
+                        conditional.taken |= condition;
                     },
                     "#else" =>
                     {
                         // Step to the next symbol
                         let token = peekable_iter.next().unwrap();
+                        Self::rest_of_line(&mut peekable_iter);
 
-                        if let Some(last_if) = self.if_stack.pop()
-                        {
-                            self.if_stack.push(!last_if);
-                        }
-                        else
+                        match self.if_stack.last_mut()
                         {
-                            return Err(PreprocessorError::syntax_error(format!("else directive cannot be used outside of a preprocessor if statement"), &token).into());
+                            Some(conditional) if conditional.seen_else =>
+                            {
+                                return Err(PreprocessorError::syntax_error(String::from("else directive cannot follow another else directive"), token).into());
+                            },
+                            Some(conditional) =>
+                            {
+                                conditional.active = !conditional.taken;
+                                conditional.taken = true;
+                                conditional.seen_else = true;
+                            },
+                            None =>
+                            {
+                                return Err(PreprocessorError::syntax_error(String::from("else directive cannot be used outside of a preprocessor if statement"), token).into());
+                            }
                         }
                     },
                     "#endif" =>
                     {
                         // Step to the next symbol
                         let token = peekable_iter.next().unwrap();
+                        Self::rest_of_line(&mut peekable_iter);
 
                         if self.if_stack.pop().is_none()
                         {
-                            return Err(PreprocessorError::syntax_error(format!("Unexpected endif directive"), &token).into());
+                            return Err(PreprocessorError::syntax_error(String::from("Unexpected endif directive"), token).into());
                         }
                     },
-                    _ => {return Err(PreprocessorError::syntax_error(format!("Unknown directive {}", directive),  &peeked_next).into())}
+                    // Line markers from preprocessed source, such as `# 12 "file.c"`, are read like #line
+                    name if name.len() > 1 && name[1..].chars().all(|c| c.is_ascii_digit()) =>
+                    {
+                        let directive = peekable_iter.next().unwrap().clone();
+                        let line = Self::rest_of_line(&mut peekable_iter);
+
+                        self.line_marker(&directive, line)?;
+                    },
+                    _ => {return Err(PreprocessorError::syntax_error(format!("Unknown directive {}", directive),  peeked_next).into())}
                 }
                 
                 continue;
             }
             else if let TokenType::Identifier(identifier) = &peeked_next.token_type
             {
-                if let Some(expand_to) = self.defines.get(identifier)
+                if identifier == "_Pragma"
                 {
-                    let orig_location = peekable_iter.next().unwrap().get_original();
+                    let operator = peekable_iter.next().unwrap().clone();
+                    result.extend(self.pragma_operator(&operator, &mut peekable_iter)?);
+                    continue;
+                }
 
-                    match expand_to
-                    {
-                        MacroReplacements::DirectReplacement(tokens) => 
-                        {
-                            let mut new_tokens = tokens.clone();
+                if self.is_defined(identifier)
+                {
+                    // Expansion may read ahead in the file to collect the arguments of function like macros
+                    let name = peekable_iter.next().unwrap().clone();
+                    let expanded = self.expand_macro(name, &mut peekable_iter)?;
 
-                            for t in &mut new_tokens
-                            {
-                                t.original_location = Some(orig_location.clone());
-                            }
+                    // Replacements may contain _Pragma operators, which are performed where the macro was used
+                    let mut expanded = self.apply_pragma_operators(expanded)?;
+                    self.attach_pending_comments(&mut expanded);
 
-                            result.append(&mut new_tokens);
-                        },
-                        MacroReplacements::FunctionLikeReplacement(_arguments, _tokens) =>
-                        {
-                            todo!()
-                        },
-                        _ => {}
-                    }
+                    result.append(&mut expanded);
                     continue;
                 }
             }
 
-            result.push(peekable_iter.next().unwrap().clone());
+            let mut token = peekable_iter.next().unwrap().clone();
+            self.attach_pending_comments(std::slice::from_mut(&mut token));
+
+            result.push(token);
         }
 
         Ok(result)
     }
 
+    /// Take the tokens remaining on the current logical line
+    pub fn rest_of_line<'x, I: Iterator<Item = &'x Token>>(iterator: &mut std::iter::Peekable<I>) -> Vec<Token>
+    {
+        let mut line = Vec::new();
+
+        while let Some(token) = iterator.peek()
+        {
+            if token.at_line_start
+            {
+                break;
+            }
+
+            line.push(iterator.next().unwrap().clone());
+        }
+
+        line
+    }
+
+    /// Put any comments kept from before directives in front of the first of the given tokens
+    fn attach_pending_comments(&mut self, tokens: &mut [Token])
+    {
+        if let Some(first) = tokens.first_mut()
+        {
+            first.comments.splice(0..0, self.pending_comments.drain(..));
+        }
+    }
+
+    /// Spell a sequence of tokens, with a single space wherever there was whitespace between them
+    pub fn spell_tokens(tokens: &[Token]) -> String
+    {
+        let mut text = String::new();
+
+        for (i, token) in tokens.iter().enumerate()
+        {
+            if i > 0 && token.leading_space
+            {
+                text.push(' ');
+            }
+
+            text += &token.code_styled();
+        }
+
+        text
+    }
+
     pub fn register_macro_empty(&mut self, name: String)
     {
         self.defines.insert(name, MacroReplacements::None);
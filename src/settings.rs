@@ -1,4 +1,73 @@
-use clap::Parser;
+use clap::{ArgEnum, CommandFactory, FromArgMatches, Parser};
+
+/// Options with GCC style single dash spellings, which are passed to the argument parser with two dashes
+///
+/// The first option which starts an argument is used, so options which start with another option come before it.
+const SINGLE_DASH_OPTIONS: [&str; 10] = ["-iquote", "-isystem", "-dM", "-MMD", "-MM", "-MD", "-MF", "-MT", "-MP", "-M"];
+
+/// Architecture which code is generated for
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target
+{
+    Riscv32,
+    Riscv64
+}
+
+impl Target
+{
+    /// Width of the integer registers in bits
+    pub fn xlen(&self) -> usize
+    {
+        match self
+        {
+            Target::Riscv32 => 32,
+            Target::Riscv64 => 64
+        }
+    }
+
+    /// Size of a pointer in bytes
+    pub fn pointer_size(&self) -> usize
+    {
+        self.xlen() / 8
+    }
+}
+
+/// Edition of the C standard which the source is written to
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standard
+{
+    C17,
+    C23
+}
+
+impl Standard
+{
+    /// Value of __STDC_VERSION__ for the standard
+    pub fn version(&self) -> &'static str
+    {
+        match self
+        {
+            Standard::C17 => "201710L",
+            Standard::C23 => "202311L"
+        }
+    }
+}
+
+/// Formats the include graph can be written in
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat
+{
+    Dot
+}
+
+/// A macro definition or removal given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroOption
+{
+    /// A definition of the form NAME or NAME=VALUE
+    Define(String),
+    Undefine(String)
+}
 
 /// Argument based compiler options
 #[derive(Parser, Debug)]
@@ -7,17 +76,112 @@ pub struct CompilerSettings
 {
     #[clap(short='T', long="tokens")]
     pub dump_tokens: bool,
-    #[clap(short='P', long="parsetree")]
+    #[clap(long="parsetree")]
     pub dump_parse_tree: bool,
-    #[clap(short='I', long="intermediate")]
+    #[clap(long="intermediate")]
     pub dump_intermediate_representation: bool,
     #[clap(short='A', long="assembly")]
     pub dump_assembly: bool,
     #[clap(short='S', long="no-out")]
     pub supress_output: bool,
+    /// Stop after preprocessing and write the preprocessed source to standard output
+    #[clap(short='E', long="preprocess")]
+    pub preprocess_only: bool,
+    /// Leave line markers out of the preprocessed source
+    #[clap(short='P', long="no-line-markers")]
+    pub no_line_markers: bool,
+    /// Keep comments in the preprocessed source
+    #[clap(short='C', long="keep-comments")]
+    pub keep_comments: bool,
+    /// Stop after preprocessing and write a #define for every macro which is still defined
+    #[clap(long="dM")]
+    pub dump_macros: bool,
+    /// Stop after preprocessing and write a make rule listing the files the source depends on
+    #[clap(long="M")]
+    pub dependencies: bool,
+    /// Like -M, but leaving out headers found in system include directories
+    #[clap(long="MM")]
+    pub user_dependencies: bool,
+    /// Write a make rule listing the files the source depends on while compiling
+    #[clap(long="MD")]
+    pub write_dependencies: bool,
+    /// Like -MD, but leaving out headers found in system include directories
+    #[clap(long="MMD")]
+    pub write_user_dependencies: bool,
+    /// File to write the make rule to, instead of standard output for -M or a .d file for -MD
+    #[clap(long="MF", value_name="FILE")]
+    pub dependency_file: Option<String>,
+    /// Target of the make rule, instead of the assembly output
+    #[clap(long="MT", value_name="TARGET")]
+    pub dependency_targets: Vec<String>,
+    /// Add a rule with no dependencies for each header, so that make doesn't fail when one is removed
+    #[clap(long="MP")]
+    pub phony_dependencies: bool,
+    /// Write the graph of included files to standard output
+    #[clap(long="include-graph", arg_enum, value_name="FORMAT")]
+    pub include_graph: Option<GraphFormat>,
+    /// Replace trigraph sequences such as ??= before tokenizing
+    #[clap(long="trigraphs")]
+    pub trigraphs: bool,
+    /// Edition of the C standard to follow, which decides the reserved words and __STDC_VERSION__
+    #[clap(long="std", arg_enum, default_value="c17")]
+    pub standard: Standard,
+    /// Architecture to generate code for
+    #[clap(long="target", arg_enum, default_value="riscv64")]
+    pub target: Target,
+    /// Add a directory to search for included headers
+    #[clap(short='I', long="include-directory", value_name="DIR")]
+    pub include_directories: Vec<String>,
+    /// Add a directory to search only for headers included with quotes
+    #[clap(long="iquote", value_name="DIR")]
+    pub quote_directories: Vec<String>,
+    /// Add a directory to search for system headers, after those given with -I
+    #[clap(long="isystem", value_name="DIR")]
+    pub system_directories: Vec<String>,
+    /// Define a macro as NAME or NAME=VALUE, which defaults to 1
+    #[clap(short='D', long="define", value_name="NAME[=VALUE]")]
+    pub defines: Vec<String>,
+    /// Remove the definition of a macro
+    #[clap(short='U', long="undefine", value_name="NAME")]
+    pub undefines: Vec<String>,
+    /// The -D and -U options in the order they were given
+    #[clap(skip)]
+    pub macro_options: Vec<MacroOption>,
     pub filenames: Vec<String>,
 }
 
+impl CompilerSettings
+{
+    /// Parse the settings from the command line, accepting the GCC spellings of options
+    pub fn from_command_line() -> Self
+    {
+        let arguments = std::env::args().flat_map(|argument|
+        {
+            // Options such as -isystem may have their value attached, or be followed by it
+            match SINGLE_DASH_OPTIONS.iter().find(|option| argument.starts_with(*option))
+            {
+                Some(option) if argument.len() > option.len() => vec![format!("-{}", option), argument[option.len()..].to_string()],
+                Some(option) => vec![format!("-{}", option)],
+                None => vec![argument]
+            }
+        });
+
+        let matches = Self::command().get_matches_from(arguments);
+        let mut settings = Self::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+
+        // Definitions and removals take effect in the order they were given
+        let defines = matches.indices_of("defines").into_iter().flatten().zip(settings.defines.iter().cloned().map(MacroOption::Define));
+        let undefines = matches.indices_of("undefines").into_iter().flatten().zip(settings.undefines.iter().cloned().map(MacroOption::Undefine));
+
+        let mut macro_options = defines.chain(undefines).collect::<Vec<_>>();
+        macro_options.sort_by_key(|(index, _)| *index);
+
+        settings.macro_options = macro_options.into_iter().map(|(_, option)| option).collect();
+
+        settings
+    }
+}
+
 impl std::default::Default for CompilerSettings
 {
     fn default() -> Self
@@ -29,7 +193,28 @@ impl std::default::Default for CompilerSettings
             dump_parse_tree: false,
             dump_intermediate_representation: false,
             dump_assembly: false,
-            supress_output: false
+            supress_output: false,
+            preprocess_only: false,
+            no_line_markers: false,
+            keep_comments: false,
+            dump_macros: false,
+            dependencies: false,
+            user_dependencies: false,
+            write_dependencies: false,
+            write_user_dependencies: false,
+            dependency_file: None,
+            dependency_targets: vec![],
+            phony_dependencies: false,
+            include_graph: None,
+            trigraphs: false,
+            standard: Standard::C17,
+            target: Target::Riscv64,
+            include_directories: vec![],
+            quote_directories: vec![],
+            system_directories: vec![],
+            defines: vec![],
+            undefines: vec![],
+            macro_options: vec![]
         }
     }
 }
\ No newline at end of file
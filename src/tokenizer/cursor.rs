@@ -0,0 +1,156 @@
+/// Trigraph sequences and the characters they replace
+const TRIGRAPHS: [(char, char); 9] =
+[
+    ('=', '#'), ('(', '['), ('/', '\\'), (')', ']'), ('\'', '^'),
+    ('<', '{'), ('!', '|'), ('>', '}'), ('-', '~')
+];
+
+/// Cursor over the raw text of a file which keeps track of its position
+///
+/// The cursor performs the first two translation phases as it goes: trigraphs are replaced when enabled, and
+/// backslash-newline pairs are removed. Positions always refer to the physical source text.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a>
+{
+    text: &'a str,
+    trigraphs: bool,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize
+}
+
+impl<'a> Cursor<'a>
+{
+    pub fn new(text: &'a str, trigraphs: bool) -> Self
+    {
+        Self
+        {
+            text,
+            trigraphs,
+            offset: 0,
+            line: 1,
+            column: 1
+        }
+    }
+
+    /// Read the character at the given physical offset after trigraph replacement, along with its length in bytes
+    fn character_at(&self, offset: usize) -> Option<(char, usize)>
+    {
+        let rest = &self.text[offset..];
+        let c = rest.chars().next()?;
+
+        if self.trigraphs && rest.starts_with("??")
+        {
+            if let Some((_, replacement)) = rest[2..].chars().next().and_then(|third| TRIGRAPHS.iter().find(|(t, _)| *t == third))
+            {
+                return Some((*replacement, 3));
+            }
+        }
+
+        Some((c, c.len_utf8()))
+    }
+
+    /// Length in bytes of the line splice starting at the given offset, if there is one
+    fn splice_at(&self, offset: usize) -> Option<usize>
+    {
+        let (c, length) = self.character_at(offset)?;
+
+        if c != '\\'
+        {
+            return None;
+        }
+
+        let rest = &self.text[offset + length..];
+
+        if rest.starts_with('\n')
+        {
+            Some(length + 1)
+        }
+        else if rest.starts_with("\r\n")
+        {
+            Some(length + 2)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Skip past any line splices at the current position
+    pub fn skip_line_splices(&mut self)
+    {
+        while let Some(length) = self.splice_at(self.offset)
+        {
+            self.offset += length;
+            self.line += 1;
+            self.column = 1;
+        }
+    }
+
+    /// Look at the next character without consuming it
+    pub fn peek(&self) -> Option<char>
+    {
+        self.peek_nth(0)
+    }
+
+    /// Look at the character n characters ahead without consuming anything
+    pub fn peek_nth(&self, n: usize) -> Option<char>
+    {
+        let mut offset = self.offset;
+
+        for i in 0..=n
+        {
+            while let Some(length) = self.splice_at(offset)
+            {
+                offset += length;
+            }
+
+            let (c, length) = self.character_at(offset)?;
+
+            if i == n
+            {
+                return Some(c);
+            }
+
+            offset += length;
+        }
+
+        unreachable!()
+    }
+
+    /// Consume the next character
+    pub fn advance(&mut self) -> Option<char>
+    {
+        self.skip_line_splices();
+
+        let (c, length) = self.character_at(self.offset)?;
+
+        self.offset += length;
+
+        if c == '\n'
+        {
+            self.line += 1;
+            self.column = 1;
+        }
+        else
+        {
+            self.column += self.text[self.offset - length..self.offset].chars().count();
+        }
+
+        Some(c)
+    }
+
+    /// Consume characters as long as they satisfy the predicate
+    pub fn eat_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> String
+    {
+        let mut result = String::new();
+
+        while let Some(c) = self.peek().filter(|c| predicate(*c))
+        {
+            result.push(c);
+            self.advance();
+        }
+
+        result
+    }
+}
@@ -0,0 +1,49 @@
+use super::Location;
+
+/// Tokenizer Error
+pub struct TokenizerError
+{
+    pub error: TokenizerErrorType,
+    pub location: Location
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenizerErrorType
+{
+    UnterminatedComment,
+    UnterminatedLiteral(String),
+    InvalidCharacter(char),
+    MalformedNumber(String),
+    MalformedLiteral(String),
+    MalformedIdentifier(String)
+}
+
+impl std::fmt::Display for TokenizerError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        match &self.error
+        {
+            TokenizerErrorType::UnterminatedComment => write!(f, "Unterminated comment")?,
+            TokenizerErrorType::UnterminatedLiteral(kind) => write!(f, "Unterminated {} literal", kind)?,
+            TokenizerErrorType::InvalidCharacter(c) => write!(f, "Invalid character {} (U+{:04X})", c.escape_debug(), *c as u32)?,
+            TokenizerErrorType::MalformedNumber(text) => write!(f, "Malformed number: {}", text)?,
+            TokenizerErrorType::MalformedLiteral(text) => write!(f, "Malformed literal: {}", text)?,
+            TokenizerErrorType::MalformedIdentifier(text) => write!(f, "Malformed identifier: {}", text)?,
+        }
+
+        write!(f, " at {}", self.location)
+    }
+}
+
+impl TokenizerError
+{
+    pub fn new(error: TokenizerErrorType, location: Location) -> Self
+    {
+        Self
+        {
+            error,
+            location
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use std::io::Read;
 
 use crate::errors::{CompilerResult, CompilerError};
+use crate::settings::CompilerSettings;
 
+use super::Cursor;
 use super::Location;
+use super::Span;
 use super::Token;
 
 #[derive(Debug, Clone)]
@@ -32,16 +35,27 @@ impl FileManager
         })
     }
 
-    /// Construct a location for the current file
-    pub fn location(&self, line: usize, column: usize) -> Location
+    /// Construct a file manager for text which doesn't come from a file on disk
+    pub fn from_text(filename: &str, raw_text: String) -> Self
     {
-        Location::construct(&self.filename, line, column)
+        FileManager
+        {
+            filename: String::from(filename),
+            raw_text,
+            tokens: None
+        }
+    }
+
+    /// Construct a location for the current file which starts at the cursor and ends at the given byte offset
+    pub fn location(&self, start: &Cursor, end: usize) -> Location
+    {
+        Location::construct(&self.filename, start.line, start.column, Span::new(start.offset, end))
     }
 
     /// Tokenize the given file
-    pub fn tokenize(&mut self) -> CompilerResult<()>
+    pub fn tokenize(&mut self, settings: &CompilerSettings) -> CompilerResult<()>
     {
-        self.tokens = Some(super::tokenize(&self)?);
+        self.tokens = Some(super::tokenize(self, settings)?);
 
         Ok(())
     }
@@ -52,31 +66,51 @@ impl FileManager
         FileTokenIterator { i: 0, file: self }
     }
 
-    /// Display an error message like interface for the given location
-    pub fn display_arrow(&self, location: &Location, length: usize)
+    /// Display an error message like interface for the given location, underlining the part of its span on the first line
+    pub fn display_arrow(&self, location: &Location)
     {
+        let span_text = self.raw_text.get(location.span.start..location.span.end).unwrap_or("");
+        let length = span_text.lines().next().unwrap_or("").chars().map(display_width).sum::<usize>().max(1);
+
         let start_line = location.line.max(3) - 3;
         let stop_line = location.line - 1;
 
         let lines = self.raw_text.lines().collect::<Vec<_>>();
         for i in start_line..=stop_line
         {
-            println!("{:5}  {}", format!("{}", i + 1), lines.get(i).unwrap_or(&""));
+            eprintln!("{:5}  {}", format!("{}", i + 1), lines.get(i).unwrap_or(&""));
         }
 
-        print!("      ");
+        eprint!("       ");
 
-        for _ in 0..location.column
+        // Columns count characters, so the padding follows the characters before the location to line up under them
+        for c in lines.get(stop_line).unwrap_or(&"").chars().take(location.column - 1)
         {
-            print!(" ");
+            match c
+            {
+                '\t' => eprint!("\t"),
+                c => eprint!("{}", " ".repeat(display_width(c)))
+            }
         }
 
         for _ in 0..length
         {
-            print!("^");
+            eprint!("^");
         }
 
-        println!();
+        eprintln!();
+    }
+}
+
+/// Number of terminal cells a character takes up, combining marks take none and East Asian wide characters take two
+fn display_width(c: char) -> usize
+{
+    match c as u32
+    {
+        0x0300..=0x036f | 0x200b..=0x200f | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f | 0x2e80..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff | 0xfe30..=0xfe4f |
+        0xff00..=0xff60 | 0xffe0..=0xffe6 | 0x1f300..=0x1f64f | 0x1f900..=0x1f9ff | 0x20000..=0x3fffd => 2,
+        _ => 1
     }
 }
 
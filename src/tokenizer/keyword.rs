@@ -0,0 +1,68 @@
+use crate::settings::Standard;
+
+/// Reserved words of the language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Keyword
+{
+    Auto, Break, Case, Char, Const, Continue, Default, Do, Double, Else, Enum, Extern, Float, For, Goto, If, Inline, Int,
+    Long, Register, Restrict, Return, Short, Signed, Sizeof, Static, Struct, Switch, Typedef, Union, Unsigned, Void,
+    Volatile, While,
+    // C11
+    Alignas, Alignof, Atomic, Bool, Complex, Generic, Imaginary, Noreturn, StaticAssert, ThreadLocal,
+    // C23
+    AlignasC23, AlignofC23, BoolC23, Constexpr, False, Nullptr, StaticAssertC23, ThreadLocalC23, True, Typeof,
+    TypeofUnqual, BitInt, Decimal32, Decimal64, Decimal128
+}
+
+/// Spellings of every keyword
+const KEYWORDS: [(&str, Keyword); 59] =
+[
+    ("auto", Keyword::Auto), ("break", Keyword::Break), ("case", Keyword::Case), ("char", Keyword::Char),
+    ("const", Keyword::Const), ("continue", Keyword::Continue), ("default", Keyword::Default), ("do", Keyword::Do),
+    ("double", Keyword::Double), ("else", Keyword::Else), ("enum", Keyword::Enum), ("extern", Keyword::Extern),
+    ("float", Keyword::Float), ("for", Keyword::For), ("goto", Keyword::Goto), ("if", Keyword::If),
+    ("inline", Keyword::Inline), ("int", Keyword::Int), ("long", Keyword::Long), ("register", Keyword::Register),
+    ("restrict", Keyword::Restrict), ("return", Keyword::Return), ("short", Keyword::Short), ("signed", Keyword::Signed),
+    ("sizeof", Keyword::Sizeof), ("static", Keyword::Static), ("struct", Keyword::Struct), ("switch", Keyword::Switch),
+    ("typedef", Keyword::Typedef), ("union", Keyword::Union), ("unsigned", Keyword::Unsigned), ("void", Keyword::Void),
+    ("volatile", Keyword::Volatile), ("while", Keyword::While),
+    ("_Alignas", Keyword::Alignas), ("_Alignof", Keyword::Alignof), ("_Atomic", Keyword::Atomic), ("_Bool", Keyword::Bool),
+    ("_Complex", Keyword::Complex), ("_Generic", Keyword::Generic), ("_Imaginary", Keyword::Imaginary),
+    ("_Noreturn", Keyword::Noreturn), ("_Static_assert", Keyword::StaticAssert), ("_Thread_local", Keyword::ThreadLocal),
+    ("alignas", Keyword::AlignasC23), ("alignof", Keyword::AlignofC23), ("bool", Keyword::BoolC23),
+    ("constexpr", Keyword::Constexpr), ("false", Keyword::False), ("nullptr", Keyword::Nullptr),
+    ("static_assert", Keyword::StaticAssertC23), ("thread_local", Keyword::ThreadLocalC23), ("true", Keyword::True),
+    ("typeof", Keyword::Typeof), ("typeof_unqual", Keyword::TypeofUnqual), ("_BitInt", Keyword::BitInt),
+    ("_Decimal32", Keyword::Decimal32), ("_Decimal64", Keyword::Decimal64), ("_Decimal128", Keyword::Decimal128)
+];
+
+impl Keyword
+{
+    /// Look up the keyword with the given spelling, if the standard reserves it
+    pub fn from_spelling(spelling: &str, standard: Standard) -> Option<Self>
+    {
+        KEYWORDS.iter().find(|(s, _)| *s == spelling).map(|(_, keyword)| *keyword).filter(|keyword| standard == Standard::C23 || !keyword.is_c23())
+    }
+
+    /// True for the keywords C23 adds, which are ordinary identifiers in earlier standards
+    pub fn is_c23(&self) -> bool
+    {
+        matches!(self, Keyword::AlignasC23 | Keyword::AlignofC23 | Keyword::BoolC23 | Keyword::Constexpr | Keyword::False |
+            Keyword::Nullptr | Keyword::StaticAssertC23 | Keyword::ThreadLocalC23 | Keyword::True | Keyword::Typeof |
+            Keyword::TypeofUnqual | Keyword::BitInt | Keyword::Decimal32 | Keyword::Decimal64 | Keyword::Decimal128)
+    }
+
+    /// Get the spelling of the keyword
+    pub fn spelling(&self) -> &'static str
+    {
+        KEYWORDS.iter().find(|(_, keyword)| keyword == self).map(|(s, _)| *s).unwrap()
+    }
+}
+
+impl std::fmt::Display for Keyword
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        write!(f, "{}", self.spelling())
+    }
+}
@@ -1,10 +1,27 @@
-/// Location object
-#[derive(Debug, Clone)]
+/// Byte offsets of the start and end of a range of source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span
+{
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span
+{
+    pub fn new(start: usize, end: usize) -> Self
+    {
+        Self { start, end }
+    }
+}
+
+/// Location object, the line and column refer to the start of the span and columns count characters rather than bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Location
 {
     pub filename: String,
     pub line: usize,
-    pub column: usize
+    pub column: usize,
+    pub span: Span
 }
 
 impl std::fmt::Display for Location
@@ -17,13 +34,13 @@ impl std::fmt::Display for Location
 
 impl Location
 {
-    /// Create a Location object from a filename, and line, column positions
-    pub fn construct(filename: &str, line: usize, column: usize) -> Self
+    /// Create a Location object from a filename, line, column positions and the span of source text it covers
+    pub fn construct(filename: &str, line: usize, column: usize, span: Span) -> Self
     {
         Location
         {
             filename: String::from(filename),
-            line, column
+            line, column, span
         }
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,24 @@
+pub mod cursor;
+pub use cursor::*;
+
+pub mod error;
+pub use error::*;
+
 pub mod file;
 pub use file::*;
 
+pub mod keyword;
+pub use keyword::*;
+
 pub mod location;
 pub use location::*;
 
+pub mod number;
+pub use number::*;
+
+pub mod punctuator;
+pub use punctuator::*;
+
 pub mod token;
 pub use token::*;
 
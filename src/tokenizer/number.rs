@@ -0,0 +1,206 @@
+use super::{Cursor, TokenType, IntegerConstant, IntegerSuffix, FloatingConstant, FloatingSuffix};
+
+/// Read a preprocessing number starting at the cursor
+pub fn lex_preprocessing_number(cursor: &mut Cursor) -> String
+{
+    let mut spelling = String::new();
+
+    // The first character is either a digit or a period
+    spelling.extend(cursor.advance());
+
+    while let Some(next) = cursor.peek()
+    {
+        // Identifier characters, periods, exponent signs and digit separators followed by a digit or nondigit all continue the number
+        if next.is_ascii_alphanumeric() || next == '_' || next == '.' ||
+            ((next == '+' || next == '-') && spelling.ends_with(['e', 'E', 'p', 'P'])) ||
+            (next == '\'' && cursor.peek_nth(1).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            spelling.push(next);
+        }
+        else
+        {
+            break;
+        }
+
+        cursor.advance();
+    }
+
+    spelling
+}
+
+/// Determine the integer suffix from its spelling
+fn parse_integer_suffix(suffix: &str) -> Option<IntegerSuffix>
+{
+    match suffix
+    {
+        "" => Some(IntegerSuffix::None),
+        "u" | "U" => Some(IntegerSuffix::Unsigned),
+        "l" | "L" => Some(IntegerSuffix::Long),
+        "ul" | "uL" | "Ul" | "UL" | "lu" | "lU" | "Lu" | "LU" => Some(IntegerSuffix::UnsignedLong),
+        "ll" | "LL" => Some(IntegerSuffix::LongLong),
+        "ull" | "uLL" | "Ull" | "ULL" | "llu" | "llU" | "LLu" | "LLU" => Some(IntegerSuffix::UnsignedLongLong),
+        _ => None
+    }
+}
+
+/// Determine the floating suffix from its spelling
+fn parse_floating_suffix(suffix: &str) -> Option<FloatingSuffix>
+{
+    match suffix
+    {
+        "" => Some(FloatingSuffix::None),
+        "f" | "F" => Some(FloatingSuffix::Float),
+        "l" | "L" => Some(FloatingSuffix::LongDouble),
+        _ => None
+    }
+}
+
+/// Remove digit separators, making sure each one sits between two digits
+fn remove_digit_separators(spelling: &str) -> Result<String, String>
+{
+    let characters = spelling.chars().collect::<Vec<_>>();
+
+    for (i, c) in characters.iter().enumerate()
+    {
+        if *c == '\''
+        {
+            let before = i.checked_sub(1).and_then(|j| characters.get(j));
+            let after = characters.get(i + 1);
+
+            if !before.is_some_and(|c| c.is_ascii_hexdigit()) || !after.is_some_and(|c| c.is_ascii_hexdigit())
+            {
+                return Err(format!("Misplaced digit separator in {}", spelling));
+            }
+        }
+    }
+
+    Ok(spelling.replace('\'', ""))
+}
+
+/// Convert a preprocessing number into an integer or floating literal
+pub fn convert_number(spelling: &str) -> Result<TokenType, String>
+{
+    let text = remove_digit_separators(spelling)?;
+
+    let lowered = text.to_ascii_lowercase();
+    let is_hex = lowered.starts_with("0x");
+    let is_binary = lowered.starts_with("0b");
+
+    let is_floating = if is_hex
+    {
+        lowered.contains('.') || lowered.contains('p')
+    }
+    else
+    {
+        !is_binary && (lowered.contains('.') || lowered.contains('e'))
+    };
+
+    if is_floating
+    {
+        convert_floating(spelling, &text, is_hex)
+    }
+    else
+    {
+        convert_integer(spelling, &text, is_hex, is_binary)
+    }
+}
+
+/// Convert the text of an integer constant
+fn convert_integer(spelling: &str, text: &str, is_hex: bool, is_binary: bool) -> Result<TokenType, String>
+{
+    let (radix, digits_start) = if is_hex
+    {
+        (16, 2)
+    }
+    else if is_binary
+    {
+        (2, 2)
+    }
+    else if text.starts_with('0')
+    {
+        (8, 0)
+    }
+    else
+    {
+        (10, 0)
+    };
+
+    // Suffix letters start at the first character which can't be a hex digit, or at the first character for other radixes
+    let digits_end = text[digits_start..].find(|c: char| if radix == 16 { !c.is_ascii_hexdigit() } else { !c.is_ascii_digit() })
+        .map(|i| i + digits_start)
+        .unwrap_or(text.len());
+
+    let digits = &text[digits_start..digits_end];
+
+    let suffix = parse_integer_suffix(&text[digits_end..])
+        .ok_or_else(|| format!("Invalid suffix {} on integer constant {}", &text[digits_end..], spelling))?;
+
+    if digits.is_empty()
+    {
+        return Err(format!("Integer constant {} has no digits", spelling));
+    }
+
+    if let Some(bad) = digits.chars().find(|c| !c.is_digit(radix))
+    {
+        return Err(format!("Invalid digit {} in integer constant {}", bad, spelling));
+    }
+
+    let value = u64::from_str_radix(digits, radix)
+        .map_err(|_| format!("Integer constant {} is too large", spelling))?;
+
+    Ok(TokenType::IntegerLiteral(IntegerConstant { value, decimal: radix == 10, suffix, spelling: spelling.to_string() }))
+}
+
+/// Convert the text of a floating constant
+fn convert_floating(spelling: &str, text: &str, is_hex: bool) -> Result<TokenType, String>
+{
+    // The suffix is whatever follows the exponent, or the final letter of the significand
+    let body_end = if is_hex
+    {
+        let exponent = text.find(['p', 'P']).ok_or_else(|| format!("Hexadecimal floating constant {} requires an exponent", spelling))?;
+        text[exponent + 1..].find(|c: char| !(c.is_ascii_digit() || c == '+' || c == '-')).map(|i| i + exponent + 1).unwrap_or(text.len())
+    }
+    else
+    {
+        text.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || c == '+' || c == '-')).unwrap_or(text.len())
+    };
+
+    let suffix = parse_floating_suffix(&text[body_end..])
+        .ok_or_else(|| format!("Invalid suffix {} on floating constant {}", &text[body_end..], spelling))?;
+
+    let body = &text[..body_end];
+
+    let value = if is_hex
+    {
+        parse_hex_floating(&body[2..])
+    }
+    else
+    {
+        body.parse::<f64>().ok()
+    }.ok_or_else(|| format!("Malformed floating constant {}", spelling))?;
+
+    Ok(TokenType::FloatingLiteral(FloatingConstant { value, suffix, spelling: spelling.to_string() }))
+}
+
+/// Parse the significand and binary exponent of a hexadecimal floating constant
+fn parse_hex_floating(body: &str) -> Option<f64>
+{
+    let (significand, exponent) = body.split_once(['p', 'P'])?;
+    let exponent = exponent.parse::<i32>().ok()?;
+
+    let (whole, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+
+    if whole.is_empty() && fraction.is_empty()
+    {
+        return None;
+    }
+
+    let mut value = 0.0;
+
+    for c in whole.chars().chain(fraction.chars())
+    {
+        value = value * 16.0 + c.to_digit(16)? as f64;
+    }
+
+    Some(value * 2f64.powi(exponent - 4 * fraction.len() as i32))
+}
@@ -0,0 +1,108 @@
+/// Punctuators of the language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Punct
+{
+    LeftBracket, RightBracket, LeftParen, RightParen, LeftBrace, RightBrace, Period, Arrow,
+    Increment, Decrement, Ampersand, Asterisk, Plus, Minus, Tilde, Exclamation,
+    Slash, Percent, ShiftLeft, ShiftRight, LessThan, GreaterThan, LessThanEqual, GreaterThanEqual, Equal, NotEqual,
+    Caret, Pipe, LogicalAnd, LogicalOr,
+    Question, Colon, Semicolon, Ellipsis,
+    Assign, MultiplyAssign, DivideAssign, ModulusAssign, AddAssign, SubtractAssign,
+    ShiftLeftAssign, ShiftRightAssign, AndAssign, XorAssign, OrAssign,
+    Comma, Hash, HashHash
+}
+
+/// Spellings of every punctuator, digraphs are listed after the canonical spellings
+const PUNCTUATORS: [(&str, Punct); 54] =
+[
+    ("[", Punct::LeftBracket), ("]", Punct::RightBracket), ("(", Punct::LeftParen), (")", Punct::RightParen),
+    ("{", Punct::LeftBrace), ("}", Punct::RightBrace), (".", Punct::Period), ("->", Punct::Arrow),
+    ("++", Punct::Increment), ("--", Punct::Decrement), ("&", Punct::Ampersand), ("*", Punct::Asterisk),
+    ("+", Punct::Plus), ("-", Punct::Minus), ("~", Punct::Tilde), ("!", Punct::Exclamation),
+    ("/", Punct::Slash), ("%", Punct::Percent), ("<<", Punct::ShiftLeft), (">>", Punct::ShiftRight),
+    ("<", Punct::LessThan), (">", Punct::GreaterThan), ("<=", Punct::LessThanEqual), (">=", Punct::GreaterThanEqual),
+    ("==", Punct::Equal), ("!=", Punct::NotEqual), ("^", Punct::Caret), ("|", Punct::Pipe),
+    ("&&", Punct::LogicalAnd), ("||", Punct::LogicalOr), ("?", Punct::Question), (":", Punct::Colon),
+    (";", Punct::Semicolon), ("...", Punct::Ellipsis), ("=", Punct::Assign),
+    ("*=", Punct::MultiplyAssign), ("/=", Punct::DivideAssign), ("%=", Punct::ModulusAssign), ("+=", Punct::AddAssign),
+    ("-=", Punct::SubtractAssign), ("<<=", Punct::ShiftLeftAssign), (">>=", Punct::ShiftRightAssign),
+    ("&=", Punct::AndAssign), ("^=", Punct::XorAssign), ("|=", Punct::OrAssign), (",", Punct::Comma),
+    ("#", Punct::Hash), ("##", Punct::HashHash),
+    ("<:", Punct::LeftBracket), (":>", Punct::RightBracket), ("<%", Punct::LeftBrace), ("%>", Punct::RightBrace),
+    ("%:", Punct::Hash), ("%:%:", Punct::HashHash)
+];
+
+impl Punct
+{
+    /// Look up the punctuator with the given spelling, including digraphs
+    pub fn from_spelling(spelling: &str) -> Option<Self>
+    {
+        PUNCTUATORS.iter().find(|(s, _)| *s == spelling).map(|(_, punct)| *punct)
+    }
+
+    /// Get the canonical spelling of the punctuator
+    pub fn spelling(&self) -> &'static str
+    {
+        PUNCTUATORS.iter().find(|(_, punct)| punct == self).map(|(s, _)| *s).unwrap()
+    }
+
+    /// Returns true if the character can start a punctuator
+    pub fn is_start(c: char) -> bool
+    {
+        PUNCTUATORS.iter().any(|(s, _)| s.starts_with(c))
+    }
+}
+
+impl std::fmt::Display for Punct
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        write!(f, "{}", self.spelling())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::settings::CompilerSettings;
+    use crate::tokenizer::{FileManager, TokenType, tokenize};
+
+    /// Tokenize some text and keep only its punctuators
+    fn punctuators(text: &str) -> Vec<Punct>
+    {
+        let tokens = tokenize(&FileManager::from_text("<test>", String::from(text)), &CompilerSettings::default()).ok().unwrap();
+
+        tokens.into_iter().filter_map(|token| match token.token_type
+        {
+            TokenType::Punct(punct) => Some(punct),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn every_punctuator()
+    {
+        // Each is placed after an identifier, so that a hash isn't read as the start of a directive
+        for (spelling, punct) in PUNCTUATORS
+        {
+            assert_eq!(punctuators(&format!("x {}", spelling)), vec![punct], "lexing {}", spelling);
+        }
+    }
+
+    #[test]
+    fn maximal_munch()
+    {
+        assert_eq!(punctuators("a+++b"), vec![Punct::Increment, Punct::Plus]);
+        assert_eq!(punctuators("x>>=y"), vec![Punct::ShiftRightAssign]);
+        assert_eq!(punctuators("x<<=y"), vec![Punct::ShiftLeftAssign]);
+        assert_eq!(punctuators("a-->b"), vec![Punct::Decrement, Punct::GreaterThan]);
+        assert_eq!(punctuators("a&&&b"), vec![Punct::LogicalAnd, Punct::Ampersand]);
+        assert_eq!(punctuators("a|||b"), vec![Punct::LogicalOr, Punct::Pipe]);
+        assert_eq!(punctuators("a..b"), vec![Punct::Period, Punct::Period]);
+        assert_eq!(punctuators("a::b"), vec![Punct::Colon, Punct::Colon]);
+        assert_eq!(punctuators("f(...)"), vec![Punct::LeftParen, Punct::Ellipsis, Punct::RightParen]);
+        assert_eq!(punctuators("x %:%: y"), vec![Punct::HashHash]);
+        assert_eq!(punctuators("a<%b%>"), vec![Punct::LeftBrace, Punct::RightBrace]);
+    }
+}
@@ -1,18 +1,181 @@
 #![allow(dead_code)]
 
+use std::collections::HashSet;
+
 use super::Location;
+use super::{Keyword, Punct, TokenizerErrorType};
 
-/// Token types and their respective associated data
+/// Suffixes which can follow an integer constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerSuffix
+{
+    None,
+    Unsigned,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong
+}
+
+/// Integer constant along with the information needed to determine its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerConstant
+{
+    pub value: u64,
+    pub decimal: bool,
+    pub suffix: IntegerSuffix,
+    pub spelling: String
+}
+
+/// Suffixes which can follow a floating constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatingSuffix
+{
+    None,
+    Float,
+    LongDouble
+}
+
+/// Floating constant
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingConstant
+{
+    pub value: f64,
+    pub suffix: FloatingSuffix,
+    pub spelling: String
+}
+
+/// Encoding prefixes which can precede string and character literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingPrefix
+{
+    None,
+    Utf8,
+    Wide,
+    Utf16,
+    Utf32
+}
+
+impl EncodingPrefix
+{
+    /// Width in bits of a single code unit in this encoding
+    pub fn unit_bits(&self) -> u32
+    {
+        match self
+        {
+            EncodingPrefix::None | EncodingPrefix::Utf8 => 8,
+            EncodingPrefix::Utf16 => 16,
+            EncodingPrefix::Wide | EncodingPrefix::Utf32 => 32
+        }
+    }
+
+    /// Encode a character as a sequence of code units
+    pub fn encode(&self, c: char, units: &mut Vec<u32>)
+    {
+        match self.unit_bits()
+        {
+            8 => units.extend(c.encode_utf8(&mut [0; 4]).bytes().map(|b| b as u32)),
+            16 => units.extend(c.encode_utf16(&mut [0; 2]).iter().map(|u| *u as u32)),
+            _ => units.push(c as u32)
+        }
+    }
+}
+
+impl std::fmt::Display for EncodingPrefix
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        match self
+        {
+            EncodingPrefix::None => write!(f, ""),
+            EncodingPrefix::Utf8 => write!(f, "u8"),
+            EncodingPrefix::Wide => write!(f, "L"),
+            EncodingPrefix::Utf16 => write!(f, "u"),
+            EncodingPrefix::Utf32 => write!(f, "U")
+        }
+    }
+}
+
+/// String or character literal, stored as code units in the encoding given by its prefix
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLiteral
+{
+    pub prefix: EncodingPrefix,
+    pub units: Vec<u32>,
+    /// The literal as it was written, which is None for literals the compiler makes itself
+    pub source: Option<String>
+}
+
+impl TextLiteral
+{
+    /// Decode the code units back into text, replacing anything which isn't valid in the encoding
+    pub fn text(&self) -> String
+    {
+        match self.prefix.unit_bits()
+        {
+            8 => String::from_utf8_lossy(&self.units.iter().map(|u| *u as u8).collect::<Vec<_>>()).into_owned(),
+            16 => String::from_utf16_lossy(&self.units.iter().map(|u| *u as u16).collect::<Vec<_>>()),
+            _ => self.units.iter().map(|u| char::from_u32(*u).unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+        }
+    }
+
+    /// Spell the literal as it would appear in code
+    ///
+    /// The spelling is made from the code units rather than the text, so that units which aren't valid in the encoding
+    /// keep their values. Anything other than printable ASCII is written as an escape sequence.
+    pub fn spelling(&self, quote: char) -> String
+    {
+        let mut body = String::new();
+        let mut after_hex_escape = false;
+
+        for unit in &self.units
+        {
+            let printable = char::from_u32(*unit).filter(|c| c.is_ascii() && !c.is_ascii_control());
+
+            match (printable, unit)
+            {
+                (Some('\\'), _) => body += "\\\\",
+                (Some(c), _) if c == quote => { body.push('\\'); body.push(c); },
+                // A hex digit straight after a hex escape would be read as part of it
+                (Some(c), _) if after_hex_escape && c.is_ascii_hexdigit() => body += &format!("\\{:o}", unit),
+                (Some(c), _) => body.push(c),
+                (None, 0x0a) => body += "\\n",
+                (None, 0x09) => body += "\\t",
+                (None, 0x0d) => body += "\\r",
+                (None, _) => body += &format!("\\x{:x}", unit)
+            }
+
+            after_hex_escape = printable.is_none() && !matches!(unit, 0x0a | 0x09 | 0x0d);
+        }
+
+        format!("{}{}{}{}", self.prefix, quote, body, quote)
+    }
+
+    /// Spell the literal exactly as it was written, if it was
+    pub fn source_spelling(&self, quote: char) -> String
+    {
+        self.source.clone().unwrap_or_else(|| self.spelling(quote))
+    }
+}
+
+/// Token types and their respective associated data
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenType
 {
     EndOfFile,
     Identifier(String),
-    IntegerLiteral(u64),
+    Keyword(Keyword),
+    IntegerLiteral(IntegerConstant),
+    FloatingLiteral(FloatingConstant),
     PreprocessorDirective(String),
-    StringLiteral(String),
-    CharacterLiteral(String),
-    Symbol(String),
+    /// A header named in angle brackets, only recognized where a directive expects one
+    HeaderName(String),
+    StringLiteral(TextLiteral),
+    CharacterLiteral(TextLiteral),
+    Punct(Punct),
+    /// Characters which don't form a valid token, their error is only reported if they survive preprocessing so
+    /// that they can still appear in skipped groups and in directives like #error
+    Other(String, TokenizerErrorType),
 }
 
 impl std::fmt::Display for TokenType
@@ -23,11 +186,15 @@ impl std::fmt::Display for TokenType
         {
             TokenType::EndOfFile => write!(f, "EOF"),
             TokenType::Identifier(name) => write!(f, "Identifier({})", name),
-            TokenType::IntegerLiteral(int) => write!(f, "IntegerLiteral({})", int),
-            TokenType::StringLiteral(string) => write!(f, "StringLiteral({})", string),
-            TokenType::CharacterLiteral(character) => write!(f, "CharacterLiteral({})", character),
+            TokenType::Keyword(keyword) => write!(f, "Keyword({})", keyword),
+            TokenType::IntegerLiteral(int) => write!(f, "IntegerLiteral({})", int.spelling),
+            TokenType::FloatingLiteral(float) => write!(f, "FloatingLiteral({})", float.spelling),
+            TokenType::StringLiteral(string) => write!(f, "StringLiteral({})", string.spelling('"')),
+            TokenType::CharacterLiteral(character) => write!(f, "CharacterLiteral({})", character.spelling('\'')),
             TokenType::PreprocessorDirective(directive) => write!(f, "PreprocessorDirective({})", directive),
-            TokenType::Symbol(symbol) => write!(f, "Symbol({})", symbol),
+            TokenType::HeaderName(name) => write!(f, "HeaderName(<{}>)", name),
+            TokenType::Punct(punct) => write!(f, "Punct({})", punct),
+            TokenType::Other(spelling, _) => write!(f, "Other({})", spelling),
         }
     }
 }
@@ -40,22 +207,55 @@ impl TokenType
         {
             TokenType::EndOfFile => String::new(),
             TokenType::Identifier(name) => name.clone(),
-            TokenType::IntegerLiteral(int) => int.to_string(),
-            TokenType::StringLiteral(string) => format!("\"{}\"", string),
-            TokenType::CharacterLiteral(character) => format!("'{}'", character),
+            TokenType::Keyword(keyword) => keyword.to_string(),
+            TokenType::IntegerLiteral(int) => int.spelling.clone(),
+            TokenType::FloatingLiteral(float) => float.spelling.clone(),
+            TokenType::StringLiteral(string) => string.spelling('"'),
+            TokenType::CharacterLiteral(character) => character.spelling('\''),
             TokenType::PreprocessorDirective(directive) => directive.clone(),
-            TokenType::Symbol(symbol) => symbol.clone(),
+            TokenType::HeaderName(name) => format!("<{}>", name),
+            TokenType::Punct(punct) => punct.to_string(),
+            TokenType::Other(spelling, _) => spelling.clone(),
         }
     }
 }
 
+/// A macro expansion which a token came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpansionFrame
+{
+    pub name: String,
+    /// Where the macro was defined, which is None for predefined macros
+    pub definition: Option<Location>,
+    /// Where the macro was invoked, which may itself be within another expansion
+    pub invocation: Location
+}
+
+/// A comment kept for the preprocessed source
+#[derive(Debug, Clone)]
+pub struct Comment
+{
+    pub text: String,
+    pub location: Location
+}
+
 /// Token object
 #[derive(Debug, Clone)]
 pub struct Token
 {
     pub token_type: TokenType,
     pub location: Location,
-    pub original_location: Option<Location>
+    pub original_location: Option<Location>,
+    /// True if this is the first token on its logical line
+    pub at_line_start: bool,
+    /// True if whitespace or a comment comes directly before this token
+    pub leading_space: bool,
+    /// Macros whose replacements this token came from, which are never replaced when this token names them
+    pub hide_set: HashSet<String>,
+    /// Macro expansions this token came from, innermost first
+    pub expansions: Vec<ExpansionFrame>,
+    /// Comments directly before this token, which are only kept when preprocessing with -C
+    pub comments: Vec<Comment>
 }
 
 impl Token
@@ -67,6 +267,11 @@ impl Token
             token_type: TokenType::EndOfFile,
             location,
             original_location: None,
+            at_line_start: true,
+            leading_space: false,
+            hide_set: HashSet::new(),
+            expansions: Vec::new(),
+            comments: Vec::new(),
         }
     }
 
@@ -77,6 +282,11 @@ impl Token
             token_type,
             location,
             original_location: None,
+            at_line_start: false,
+            leading_space: false,
+            hide_set: HashSet::new(),
+            expansions: Vec::new(),
+            comments: Vec::new(),
         }
     }
 
@@ -90,16 +300,36 @@ impl Token
         matches!(self.token_type, TokenType::Identifier(_))
     }
 
+    pub fn is_keyword(&self, keyword: Keyword) -> bool
+    {
+        self.token_type == TokenType::Keyword(keyword)
+    }
+
+    pub fn is_punct(&self, punct: Punct) -> bool
+    {
+        self.token_type == TokenType::Punct(punct)
+    }
+
     pub fn is_integer(&self) -> bool
     {
         matches!(self.token_type, TokenType::IntegerLiteral(_))
     }
 
+    pub fn is_floating(&self) -> bool
+    {
+        matches!(self.token_type, TokenType::FloatingLiteral(_))
+    }
+
     pub fn is_preprocessor_directive(&self) -> bool
     {
         matches!(self.token_type, TokenType::PreprocessorDirective(_))
     }
 
+    pub fn is_other(&self) -> bool
+    {
+        matches!(self.token_type, TokenType::Other(..))
+    }
+
     pub fn get_original(&self) -> &Location
     {
         self.original_location.as_ref().unwrap_or(&self.location)
@@ -120,4 +350,16 @@ impl Token
     {
         self.token_type.code_styled()
     }
+
+    /// Spell the token as it was written, which only differs from the code styled spelling for literals, whose escape
+    /// sequences are kept as they are
+    pub fn source_spelling(&self) -> String
+    {
+        match &self.token_type
+        {
+            TokenType::StringLiteral(literal) => literal.source_spelling('"'),
+            TokenType::CharacterLiteral(literal) => literal.source_spelling('\''),
+            token_type => token_type.code_styled()
+        }
+    }
 }
\ No newline at end of file
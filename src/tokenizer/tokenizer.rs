@@ -1,154 +1,652 @@
-use super::Token;
+use super::Cursor;
+use super::{Comment, Token};
 use super::TokenType;
+use super::{EncodingPrefix, TextLiteral};
+use super::{Keyword, Punct};
 use super::FileManager;
-use super::Location;
+use super::{TokenizerError, TokenizerErrorType};
+use super::{lex_preprocessing_number, convert_number};
 
 use crate::errors::*;
+use crate::settings::{CompilerSettings, Standard};
 
-const SINGLE_CHAR_SYMBOLS: [&str; 17] = ["+", "-", "*", "/", ";", "(", ")", "{", "}", "=", ",", "<", ">", "!", "~", "*", "&"];
-const ONLY_SINGLE_CHAR_SYMBOLS: [&str; 7] = [";", "(", ")", "{", "}", ",", "~"];
-const ONLY_DOUBLE_CHAR_SYMBOLS: [&str; 9] = ["++", "--", "==", "+=", "-=", "*=", "/=", "<=", ">="];
-const DOUBLE_CHAR_SYMBOLS: [&str; 14] = ["++", "--", "==", "+=", "-=", "*=", "/=", "<=", ">=", "<<", ">>", "<<=", ">>=", "!="];
+/// Returns true if the character can start an identifier
+fn is_identifier_start(c: char) -> bool
+{
+    c.is_alphabetic() || c == '_'
+}
+
+/// Returns true if the character can appear in an identifier
+fn is_identifier_character(c: char) -> bool
+{
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns true if a universal character name starts at the cursor
+fn at_universal_character_name(cursor: &Cursor) -> bool
+{
+    cursor.peek() == Some('\\') && matches!(cursor.peek_nth(1), Some('u') | Some('U'))
+}
 
-/// Convert a string into a token_type
-pub fn convert_to_token_type(s: String) -> TokenType
+/// Decode the digits of a universal character name, the leading backslash and letter have already been consumed
+fn decode_universal_character_name(cursor: &mut Cursor, digits: usize) -> Result<char, String>
 {
-    if let Ok(number) = s.parse::<u64>()
+    let mut spelling = String::new();
+
+    for _ in 0..digits
     {
-        TokenType::IntegerLiteral(number)
+        match cursor.peek().filter(|c| c.is_ascii_hexdigit())
+        {
+            Some(c) =>
+            {
+                spelling.push(c);
+                cursor.advance();
+            },
+            None => return Err(format!("Incomplete universal character name \\{}{}", if digits == 4 { 'u' } else { 'U' }, spelling))
+        }
     }
-    else if s.starts_with("#")
+
+    let value = u32::from_str_radix(&spelling, 16).unwrap();
+
+    // Only a few basic characters may be named, and surrogates are never characters
+    match char::from_u32(value)
     {
-        TokenType::PreprocessorDirective(s)
+        Some(c) if value >= 0xa0 || c == '$' || c == '@' || c == '`' => Ok(c),
+        _ => Err(format!("Universal character name \\{}{} does not name a valid character", if digits == 4 { 'u' } else { 'U' }, spelling))
     }
-    else if s.starts_with("\"") && s.ends_with("\"")
+}
+
+/// Read an identifier starting at the cursor, universal character names are replaced by the characters they name
+fn lex_identifier(cursor: &mut Cursor) -> Result<String, TokenizerErrorType>
+{
+    let mut name = String::new();
+
+    loop
     {
-        let l = s.len();
-        TokenType::StringLiteral(s[1..l-1].to_string())
+        if at_universal_character_name(cursor)
+        {
+            cursor.advance();
+            let digits = if cursor.advance() == Some('u') { 4 } else { 8 };
+
+            let c = decode_universal_character_name(cursor, digits).map_err(TokenizerErrorType::MalformedIdentifier)?;
+
+            if !is_identifier_character(c) || (name.is_empty() && !is_identifier_start(c))
+            {
+                return Err(TokenizerErrorType::MalformedIdentifier(format!("Character {} cannot appear in an identifier", c)));
+            }
+
+            name.push(c);
+        }
+        else if let Some(c) = cursor.peek().filter(|c| is_identifier_character(*c))
+        {
+            name.push(c);
+            cursor.advance();
+        }
+        else
+        {
+            return Ok(name);
+        }
     }
-    else if SINGLE_CHAR_SYMBOLS.contains(&s.as_str()) || DOUBLE_CHAR_SYMBOLS.contains(&s.as_str())
+}
+
+/// Determine the encoding prefix of the string or character literal starting at the cursor, along with its length
+fn literal_prefix(cursor: &Cursor) -> Option<(EncodingPrefix, usize)>
+{
+    let is_quote = |c: Option<char>| c == Some('"') || c == Some('\'');
+
+    match cursor.peek()?
     {
-        TokenType::Symbol(s)
+        '"' | '\'' => Some((EncodingPrefix::None, 0)),
+        'L' if is_quote(cursor.peek_nth(1)) => Some((EncodingPrefix::Wide, 1)),
+        'U' if is_quote(cursor.peek_nth(1)) => Some((EncodingPrefix::Utf32, 1)),
+        'u' if is_quote(cursor.peek_nth(1)) => Some((EncodingPrefix::Utf16, 1)),
+        'u' if cursor.peek_nth(1) == Some('8') && is_quote(cursor.peek_nth(2)) => Some((EncodingPrefix::Utf8, 2)),
+        _ => None
     }
-    else
+}
+
+/// Check if the tokens so far leave the tokenizer where a header name in angle brackets is expected, which is after an
+/// include directive or inside the parentheses of __has_include
+fn expects_header_name(tokens: &[Token], at_line_start: bool) -> bool
+{
+    match tokens
     {
-        TokenType::Identifier(s)
+        [.., directive] if !at_line_start => matches!(&directive.token_type, TokenType::PreprocessorDirective(name) if name == "#include" || name == "#include_next"),
+        [.., operator, paren] if paren.is_punct(Punct::LeftParen) =>
+        {
+            matches!(&operator.token_type, TokenType::Identifier(name) if name == "__has_include" || name == "__has_include_next")
+        },
+        _ => false
     }
 }
 
-/// Push a token to the result vector
-pub fn push_token(current: &mut String, last_location: &mut Option<Location>, result: &mut Vec<Token>)
+/// Read a header name in angle brackets, leaving the cursor alone if there is no closing bracket on the line
+fn lex_header_name(cursor: &mut Cursor) -> Option<String>
 {
-    if current.len() != 0 && last_location.is_some()
+    let mut lookahead = cursor.clone();
+    lookahead.advance();
+
+    let name = lookahead.eat_while(|c| c != '>' && c != '\n');
+
+    if lookahead.advance() != Some('>')
     {
-        result.push(Token::construct(convert_to_token_type(current.clone()), last_location.take().unwrap()));
-        *current = String::new();
+        return None;
     }
+
+    *cursor = lookahead;
+
+    Some(name)
 }
 
-/// Tokenize a file into usable tokens
-pub fn tokenize(file: &FileManager) -> CompilerResult<Vec<Token>>
+/// Read the longest punctuator starting at the cursor, digraphs give the punctuators they are alternative spellings of
+fn lex_punctuator(cursor: &mut Cursor) -> Option<Punct>
 {
-    let mut result = Vec::new();
+    let mut candidates = vec![String::new()];
 
-    trace!("Tokenizing {}", file.filename);
+    for i in 0..4
+    {
+        match cursor.peek_nth(i)
+        {
+            Some(c) =>
+            {
+                let mut next = candidates.last().unwrap().clone();
+                next.push(c);
+                candidates.push(next);
+            },
+            None => break
+        }
+    }
 
-    let mut line_count = 1;
-    let mut in_multiline_comment = false;
-    // TODO: Add string and character tokenization
-    // let mut in_string = false;
-    // let mut in_character = false;
+    let (spelling, punctuator) = candidates.into_iter().rev().find_map(|candidate| Punct::from_spelling(&candidate).map(|p| (candidate, p)))?;
 
-    for line in file.raw_text.lines()
+    for _ in 0..spelling.chars().count()
     {
-        let mut column_count = 0;
-        let mut last_location: Option<Location> = None;
-        let mut current = String::new();
+        cursor.advance();
+    }
 
-        for c in line.chars()
+    Some(punctuator)
+}
+
+/// Decoded escape sequence, numeric escapes give a code unit directly while the rest give a character
+enum Escape
+{
+    Unit(u32),
+    Character(char)
+}
+
+/// Decode the remainder of an escape sequence, the leading backslash has already been consumed
+fn decode_escape_sequence(cursor: &mut Cursor) -> Result<Escape, String>
+{
+    let c = cursor.advance().unwrap();
+
+    let value = match c
+    {
+        'n' => '\n' as u32,
+        't' => '\t' as u32,
+        'r' => '\r' as u32,
+        'a' => 0x07,
+        'b' => 0x08,
+        'f' => 0x0c,
+        'v' => 0x0b,
+        'e' => 0x1b,
+        '\\' | '\'' | '"' | '?' => c as u32,
+        '0'..='7' =>
         {
-            column_count += 1;
+            // Octal escapes consist of at most three octal digits
+            let mut value = c.to_digit(8).unwrap();
 
-            if in_multiline_comment
+            for _ in 0..2
             {
-                if c == '*' && current.len() == 0
-                {
-                    current += "*";
-                }
-                else if c == '/' && current == "*"
-                {
-                    current = String::new();
-                    last_location = None;
-                    in_multiline_comment = false;
-                }
-                else
+                match cursor.peek().and_then(|c| c.to_digit(8))
                 {
-                    current = String::new();
+                    Some(digit) =>
+                    {
+                        value = value * 8 + digit;
+                        cursor.advance();
+                    },
+                    None => break
                 }
-                continue;
             }
 
-            if c == ' ' || c == '\t' || c == '\r' || c == '\n'
+            return Ok(Escape::Unit(value));
+        },
+        'x' =>
+        {
+            // Hexadecimal escapes consume every following hex digit
+            let mut value: u64 = 0;
+            let mut digits = 0;
+
+            while let Some(digit) = cursor.peek().and_then(|c| c.to_digit(16))
             {
-                push_token(&mut current, &mut last_location, &mut result);
-                continue;
+                value = (value * 16 + digit as u64).min(u64::MAX >> 4);
+                digits += 1;
+                cursor.advance();
             }
-            else
+
+            if digits == 0
             {
-                let s = String::from(c);
-                let s = s.as_str();
+                return Err(String::from("Hexadecimal escape sequence has no digits"));
+            }
+
+            return Ok(Escape::Unit(value.min(u32::MAX as u64) as u32));
+        },
+        'u' => return decode_universal_character_name(cursor, 4).map(Escape::Character),
+        'U' => return decode_universal_character_name(cursor, 8).map(Escape::Character),
+        _ =>
+        {
+            warn!("Unknown escape sequence \\{}", c);
+            c as u32
+        }
+    };
+
+    Ok(Escape::Character(char::from_u32(value).unwrap()))
+}
+
+/// Read the body of a string or character literal up to the closing quote, encoding it with the given prefix
+///
+/// Bad escape sequences don't stop the literal from being read, so that lexing can carry on after its closing quote.
+fn lex_quoted_literal(cursor: &mut Cursor, quote: char, prefix: EncodingPrefix) -> Result<Vec<u32>, TokenizerErrorType>
+{
+    let kind = if quote == '"' { "string" } else { "character" };
+    let mut units = Vec::new();
+    let mut error = None;
+
+    // Step past the opening quote
+    cursor.advance();
+
+    loop
+    {
+        // Literals can't span multiple lines
+        let c = match cursor.peek()
+        {
+            Some('\n') | None => return Err(TokenizerErrorType::UnterminatedLiteral(kind.to_string())),
+            Some(c) => c
+        };
+
+        cursor.advance();
+
+        if c == quote
+        {
+            return match error
+            {
+                Some(message) => Err(TokenizerErrorType::MalformedLiteral(message)),
+                None => Ok(units)
+            };
+        }
+        else if c == '\\'
+        {
+            if matches!(cursor.peek(), Some('\n') | None)
+            {
+                return Err(TokenizerErrorType::UnterminatedLiteral(kind.to_string()));
+            }
+
+            match decode_escape_sequence(cursor)
+            {
+                Ok(Escape::Unit(unit)) if prefix.unit_bits() == 32 || unit < (1 << prefix.unit_bits()) => units.push(unit),
+                Ok(Escape::Unit(_)) => { error.get_or_insert(format!("Escape sequence out of range in {} literal", kind)); },
+                Ok(Escape::Character(c)) => prefix.encode(c, &mut units),
+                Err(message) => { error.get_or_insert(message); }
+            }
+        }
+        else
+        {
+            prefix.encode(c, &mut units);
+        }
+    }
+}
+
+/// Escape a decoded literal so it can be displayed as it would appear in code
+pub fn escape_literal(body: &str, quote: char) -> String
+{
+    let mut result = String::new();
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next()
+    {
+        match c
+        {
+            '\n' => result += "\\n",
+            '\t' => result += "\\t",
+            '\r' => result += "\\r",
+            '\\' => result += "\\\\",
+            c if c == quote => { result.push('\\'); result.push(c); },
+            // Octal escapes are only padded when a digit follows, which would otherwise be read as part of them
+            c if ((c as u32) < 0x20 || c as u32 == 0x7f) && chars.peek().is_some_and(|n| ('0'..='7').contains(n)) => result += &format!("\\{:03o}", c as u32),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => result += &format!("\\{:o}", c as u32),
+            c => result.push(c)
+        }
+    }
 
-                let mut next_current = current.clone();
-                next_current.push(c);
+    result
+}
+
+/// Report the lexical errors of any other tokens which are left once preprocessing is done, all of them together
+pub fn check_other_tokens(tokens: &[Token]) -> CompilerResult<()>
+{
+    let errors: Vec<_> = tokens.iter().filter_map(|token| match &token.token_type
+    {
+        TokenType::Other(_, error) => Some(TokenizerError::new(error.clone(), token.location.clone())),
+        _ => None
+    }).collect();
+
+    if errors.is_empty()
+    {
+        Ok(())
+    }
+    else
+    {
+        Err(CompilerError::TokenizerError(errors))
+    }
+}
+
+/// Encode the code units of an unprefixed literal with another prefix
+///
+/// Characters are encoded again, but bytes which aren't valid UTF-8, which can only come from escape sequences, keep
+/// their values as code units.
+fn reencode_units(units: &[u32], prefix: EncodingPrefix) -> Vec<u32>
+{
+    let bytes = units.iter().map(|u| *u as u8).collect::<Vec<_>>();
+    let mut result = Vec::new();
+
+    for chunk in bytes.utf8_chunks()
+    {
+        chunk.valid().chars().for_each(|c| prefix.encode(c, &mut result));
+        result.extend(chunk.invalid().iter().map(|b| *b as u32));
+    }
+
+    result
+}
 
-                if next_current == "/*"
+/// Concatenate adjacent string literals into a single string literal token
+///
+/// A literal without a prefix takes on the prefix of the literal it is joined with, but two different prefixes can't be mixed.
+pub fn concatenate_string_literals(tokens: Vec<Token>) -> CompilerResult<Vec<Token>>
+{
+    let mut result: Vec<Token> = Vec::new();
+
+    for token in tokens
+    {
+        if let TokenType::StringLiteral(next) = &token.token_type
+        {
+            if let Some(Token { token_type: TokenType::StringLiteral(last), location, .. }) = result.last_mut()
+            {
+                if last.prefix == EncodingPrefix::None && next.prefix != EncodingPrefix::None
                 {
-                    current = String::new();
-                    last_location = None;
-                    in_multiline_comment = true;
-                    continue;
+                    last.units = reencode_units(&last.units, next.prefix);
+                    last.prefix = next.prefix;
                 }
 
-                if next_current == "//"
+                // The joined literal was never written as a whole
+                last.source = None;
+
+                if next.prefix == last.prefix
                 {
-                    current = String::new();
-                    break;
+                    last.units.extend(&next.units);
                 }
-
-                if ONLY_DOUBLE_CHAR_SYMBOLS.contains(&current.as_str()) || ONLY_SINGLE_CHAR_SYMBOLS.contains(&s) || 
-                    (SINGLE_CHAR_SYMBOLS.contains(&current.as_str()) && !DOUBLE_CHAR_SYMBOLS.contains(&next_current.as_str())) || 
-                    (DOUBLE_CHAR_SYMBOLS.contains(&current.as_str()) && !DOUBLE_CHAR_SYMBOLS.contains(&next_current.as_str()))
+                else if next.prefix == EncodingPrefix::None
                 {
-                    push_token(&mut current, &mut last_location, &mut result);
+                    last.units.extend(reencode_units(&next.units, last.prefix));
+                }
+                else
+                {
+                    let error = TokenizerErrorType::MalformedLiteral(format!("Cannot concatenate {} with a literal of a different encoding prefix", token.code_styled()));
+                    return Err(TokenizerError::new(error, token.location.clone()).into());
                 }
 
-                if SINGLE_CHAR_SYMBOLS.contains(&s) && !SINGLE_CHAR_SYMBOLS.contains(&current.as_str().chars().last().map(|c| c.to_string()).unwrap_or(String::new()).as_str())
+                if location.filename == token.location.filename
                 {
-                    push_token(&mut current, &mut last_location, &mut result);
+                    location.span.end = token.location.span.end;
                 }
 
-                if last_location.is_none()
+                continue;
+            }
+        }
+
+        result.push(token);
+    }
+
+    Ok(result)
+}
+
+/// Turn identifiers which are reserved words into keywords, which is only done once preprocessing is complete
+pub fn identify_keywords(tokens: Vec<Token>, standard: Standard) -> Vec<Token>
+{
+    tokens.into_iter().map(|mut token|
+    {
+        if let TokenType::Identifier(name) = &token.token_type
+        {
+            if let Some(keyword) = Keyword::from_spelling(name, standard)
+            {
+                token.token_type = TokenType::Keyword(keyword);
+            }
+        }
+
+        token
+    }).collect()
+}
+
+/// Spell the text from a cursor up to a byte offset, with trigraphs replaced and line splices removed
+fn logical_spelling(start: &Cursor, end: usize) -> String
+{
+    let mut cursor = start.clone();
+    let mut spelling = String::new();
+
+    while cursor.offset < end
+    {
+        match cursor.advance()
+        {
+            Some(c) => spelling.push(c),
+            None => break
+        }
+    }
+
+    spelling
+}
+
+/// Keep a comment which ends at the cursor, trailing carriage returns are left out of line comments
+fn keep_comment(file: &FileManager, start: &Cursor, end: &Cursor) -> Comment
+{
+    Comment { text: file.raw_text[start.offset..end.offset].trim_end().to_string(), location: file.location(start, end.offset) }
+}
+
+/// Tokenize a file into usable tokens
+///
+/// Only unterminated comments stop tokenizing, anything else which can't be lexed becomes an other token whose error is
+/// reported by `check_other_tokens` once preprocessing is done.
+pub fn tokenize(file: &FileManager, settings: &CompilerSettings) -> CompilerResult<Vec<Token>>
+{
+    let mut result = Vec::new();
+    let mut errors = Vec::new();
+
+    trace!("Tokenizing {}", file.filename);
+
+    let mut cursor = Cursor::new(&file.raw_text, settings.trigraphs);
+
+    // Used to determine if a hash is the first token on its line
+    let mut at_line_start = true;
+    let mut leading_space = false;
+
+    // Comments are only kept with -C, and never on directive lines since the directives aren't part of the output
+    let mut comments = Vec::new();
+    let mut in_directive = false;
+
+    loop
+    {
+        // Tokens start after any line splices so that they report where their first character is
+        cursor.skip_line_splices();
+
+        let start = cursor.clone();
+
+        let c = match cursor.peek()
+        {
+            Some(c) => c,
+            None => break
+        };
+
+        if c.is_whitespace()
+        {
+            cursor.advance();
+            leading_space = true;
+
+            if c == '\n'
+            {
+                at_line_start = true;
+                in_directive = false;
+            }
+
+            continue;
+        }
+
+        // Line comments consume the rest of the line, leaving the newline in place
+        if c == '/' && cursor.peek_nth(1) == Some('/')
+        {
+            cursor.eat_while(|c| c != '\n');
+            leading_space = true;
+
+            if settings.keep_comments && !in_directive
+            {
+                comments.push(keep_comment(file, &start, &cursor));
+            }
+
+            continue;
+        }
+
+        // Block comments may span multiple lines, but a comment is a single space so it never ends a directive
+        if c == '/' && cursor.peek_nth(1) == Some('*')
+        {
+            cursor.advance();
+            cursor.advance();
+
+            while cursor.peek().is_some() && !(cursor.peek() == Some('*') && cursor.peek_nth(1) == Some('/'))
+            {
+                // Outside a directive, the newline still lets a hash after the comment start one
+                if cursor.advance() == Some('\n') && !in_directive
                 {
-                    last_location = Some(file.location(line_count, column_count));
+                    at_line_start = true;
                 }
+            }
+
+            // Point at the opening of the comment rather than the end of the file
+            if cursor.peek().is_none()
+            {
+                errors.push(TokenizerError::new(TokenizerErrorType::UnterminatedComment, file.location(&start, start.offset + 2)));
+                break;
+            }
 
-                if ONLY_SINGLE_CHAR_SYMBOLS.contains(&s)
+            cursor.advance();
+            cursor.advance();
+            leading_space = true;
+
+            if settings.keep_comments && !in_directive
+            {
+                comments.push(keep_comment(file, &start, &cursor));
+            }
+
+            continue;
+        }
+
+        let header_name = if c == '<' && expects_header_name(&result, at_line_start)
+        {
+            lex_header_name(&mut cursor)
+        }
+        else
+        {
+            None
+        };
+
+        let token_type = if let Some(name) = header_name
+        {
+            Ok(TokenType::HeaderName(name))
+        }
+        else if c.is_ascii_digit() || (c == '.' && cursor.peek_nth(1).is_some_and(|n| n.is_ascii_digit()))
+        {
+            let spelling = lex_preprocessing_number(&mut cursor);
+
+            convert_number(&spelling).map_err(TokenizerErrorType::MalformedNumber)
+        }
+        else if let Some((prefix, length)) = literal_prefix(&cursor)
+        {
+            for _ in 0..length
+            {
+                cursor.advance();
+            }
+
+            let quote = cursor.peek().unwrap();
+
+            lex_quoted_literal(&mut cursor, quote, prefix).map(|units|
+            {
+                let source = Some(logical_spelling(&start, cursor.offset));
+
+                if quote == '"'
                 {
-                    push_token(&mut String::from(c), &mut last_location, &mut result)
+                    TokenType::StringLiteral(TextLiteral { prefix, units, source })
                 }
                 else
                 {
-                    current.push(c);
+                    TokenType::CharacterLiteral(TextLiteral { prefix, units, source })
                 }
+            })
+        }
+        else if Punct::is_start(c)
+        {
+            let punctuator = lex_punctuator(&mut cursor).unwrap();
+
+            // A hash at the start of a line introduces a preprocessor directive
+            if punctuator == Punct::Hash && at_line_start
+            {
+                cursor.eat_while(|c| c == ' ' || c == '\t');
+
+                let name = cursor.eat_while(is_identifier_character);
+
+                // A hash on its own is the null directive, which has no effect
+                if name.is_empty()
+                {
+                    continue;
+                }
+
+                Ok(TokenType::PreprocessorDirective(format!("#{}", name)))
             }
+            else
+            {
+                Ok(TokenType::Punct(punctuator))
+            }
+        }
+        else if is_identifier_start(c) || at_universal_character_name(&cursor)
+        {
+            lex_identifier(&mut cursor).map(TokenType::Identifier)
         }
+        else
+        {
+            // Anything else can't start a token
+            cursor.advance();
+
+            Err(TokenizerErrorType::InvalidCharacter(c))
+        };
+
+        let token_type = token_type.unwrap_or_else(|error| TokenType::Other(file.raw_text[start.offset..cursor.offset].to_string(), error));
 
-        push_token(&mut current, &mut last_location, &mut result);
+        let mut token = Token::construct(token_type, file.location(&start, cursor.offset));
+        token.at_line_start = at_line_start;
+        token.leading_space = leading_space;
+        token.comments = std::mem::take(&mut comments);
 
-        line_count += 1;
+        in_directive |= token.is_preprocessor_directive();
+
+        result.push(token);
+
+        at_line_start = false;
+        leading_space = false;
     }
 
-    result.push(Token::eof(file.location(line_count, 1)));
+    if !errors.is_empty()
+    {
+        return Err(CompilerError::TokenizerError(errors));
+    }
+
+    let mut eof = Token::eof(file.location(&cursor, cursor.offset));
+    eof.comments = comments;
+
+    result.push(eof);
 
     Ok(result)
-}
\ No newline at end of file
+}